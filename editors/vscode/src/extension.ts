@@ -1,5 +1,6 @@
 import * as vscode from "vscode";
 import {
+  ExecuteCommandRequest,
   LanguageClient,
   LanguageClientOptions,
   ServerOptions,
@@ -18,6 +19,8 @@ export function activate(context: vscode.ExtensionContext) {
 
   const clientOptions: LanguageClientOptions = {
     documentSelector: [{ scheme: "file", language: "rholang" }],
+    initializationOptions: config,
+    synchronize: { configurationSection: "rholang.lsp" },
   };
 
   client = new LanguageClient(
@@ -28,6 +31,57 @@ export function activate(context: vscode.ExtensionContext) {
   );
 
   client.start();
+
+  context.subscriptions.push(
+    vscode.commands.registerCommand("rholang.changeSignature", changeSignature)
+  );
+}
+
+/** Ask for the new formals of the contract under the cursor and let the server rewrite it and its callers. */
+async function changeSignature() {
+  const editor = vscode.window.activeTextEditor;
+  if (!editor || !client) {
+    return;
+  }
+  const input = await vscode.window.showInputBox({
+    prompt: "New formals, comma-separated. Existing formals keep their arguments; new ones get Nil.",
+    placeHolder: "@x, ret",
+  });
+  if (input === undefined) {
+    return;
+  }
+  await client.sendRequest(ExecuteCommandRequest.type, {
+    command: "rholang.applyChangeSignature",
+    arguments: [
+      {
+        uri: editor.document.uri.toString(),
+        position: client.code2ProtocolConverter.asPosition(editor.selection.active),
+        formals: splitFormals(input),
+      },
+    ],
+  });
+}
+
+/** Split on top-level commas, so patterns such as `@[a, b]` stay whole. */
+function splitFormals(input: string): string[] {
+  const formals: string[] = [];
+  let depth = 0;
+  let current = "";
+  for (const c of input) {
+    if ("([{".includes(c)) {
+      depth++;
+    } else if (")]}".includes(c)) {
+      depth--;
+    }
+    if (c === "," && depth === 0) {
+      formals.push(current.trim());
+      current = "";
+    } else {
+      current += c;
+    }
+  }
+  formals.push(current.trim());
+  return formals.filter((f) => f.length > 0);
 }
 
 export function deactivate(): Thenable<void> | undefined {
@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::scope::{Channel, Scopes};
+use crate::syntax::{
+    self, bind_source, has_remainder, is_bind, is_send, names_list, send_args, send_channel,
+};
+
+/// Number of names a receiver (contract or bind) accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arity {
+    pub count: usize,
+    /// `...@rest` accepts any number of extra arguments.
+    pub variadic: bool,
+}
+
+impl Arity {
+    pub fn of_names(names: Option<Node>) -> Self {
+        match names {
+            Some(names) => Arity {
+                count: names_list(names).len(),
+                variadic: has_remainder(names),
+            },
+            None => Arity {
+                count: 0,
+                variadic: false,
+            },
+        }
+    }
+
+    pub fn accepts(self, n: usize) -> bool {
+        n == self.count || (self.variadic && n > self.count)
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plural = if self.count == 1 { "" } else { "s" };
+        if self.variadic {
+            write!(f, "at least {} argument{plural}", self.count)
+        } else {
+            write!(f, "{} argument{plural}", self.count)
+        }
+    }
+}
+
+/// A contract declared on a free name, visible to sends in other files.
+#[derive(Debug, Clone)]
+pub struct ContractSig {
+    pub name: String,
+    pub arity: Arity,
+    pub location: Location,
+}
+
+#[derive(Default)]
+struct Sites<'a> {
+    contracts: Vec<Node<'a>>,
+    sends: Vec<Node<'a>>,
+    binds: Vec<Node<'a>>,
+}
+
+fn collect_sites<'a>(node: Node<'a>, sites: &mut Sites<'a>) {
+    if node.kind() == "contract" {
+        sites.contracts.push(node);
+    } else if is_send(node) {
+        sites.sends.push(node);
+    } else if is_bind(node) {
+        sites.binds.push(node);
+    }
+    for child in syntax::named_children(node) {
+        collect_sites(child, sites);
+    }
+}
+
+/// Contracts in this document whose name is free, so other files may send to them.
+pub fn exported_contracts(doc: &Document, scopes: &Scopes, uri: &Url) -> Vec<ContractSig> {
+    let mut sites = Sites::default();
+    collect_sites(doc.tree.root_node(), &mut sites);
+    sites
+        .contracts
+        .into_iter()
+        .filter_map(|contract| {
+            let name = contract.child_by_field_name("name")?;
+            match scopes.channel(name)? {
+                Channel::Free(text) => Some(ContractSig {
+                    name: text.to_string(),
+                    arity: Arity::of_names(contract.child_by_field_name("formals")),
+                    location: Location {
+                        uri: uri.clone(),
+                        range: node_range(name),
+                    },
+                }),
+                Channel::Bound(_) => None,
+            }
+        })
+        .collect()
+}
+
+/// Whether the document sends on any of the free `names`, so its arity checks depend
+/// on the contracts other files declare on them.
+pub fn sends_to_any(doc: &Document, names: &HashSet<String>) -> bool {
+    let mut sites = Sites::default();
+    collect_sites(doc.tree.root_node(), &mut sites);
+    let scopes = Scopes::analyze(doc);
+    sites.sends.iter().any(|send| {
+        matches!(
+            send_channel(*send).and_then(|c| scopes.channel(c)),
+            Some(Channel::Free(name)) if names.contains(name)
+        )
+    })
+}
+
+/// Report sends whose argument count matches no contract on the channel, and
+/// receives whose arity matches no send on the channel.
+pub fn check(
+    doc: &Document,
+    scopes: &Scopes,
+    uri: &Url,
+    workspace: &[ContractSig],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let source = doc.source.as_str();
+    let mut sites = Sites::default();
+    collect_sites(doc.tree.root_node(), &mut sites);
+
+    let local: Vec<(Channel, ContractSig)> = sites
+        .contracts
+        .iter()
+        .filter_map(|contract| {
+            let name = contract.child_by_field_name("name")?;
+            let key = scopes.channel(name)?;
+            Some((
+                key,
+                ContractSig {
+                    name: syntax::text(name, source).to_string(),
+                    arity: Arity::of_names(contract.child_by_field_name("formals")),
+                    location: Location {
+                        uri: uri.clone(),
+                        range: node_range(name),
+                    },
+                },
+            ))
+        })
+        .collect();
+
+    let sends: Vec<(Channel, Node, usize)> = sites
+        .sends
+        .iter()
+        .filter_map(|send| {
+            let key = scopes.channel(send_channel(*send)?)?;
+            Some((key, *send, send_args(*send).len()))
+        })
+        .collect();
+
+    for &(key, send, argc) in &sends {
+        let mut candidates: Vec<&ContractSig> = local
+            .iter()
+            .filter(|(k, _)| *k == key)
+            .map(|(_, sig)| sig)
+            .collect();
+        if let (true, Channel::Free(name)) = (candidates.is_empty(), key) {
+            candidates = workspace.iter().filter(|sig| sig.name == name).collect();
+        }
+        if candidates.is_empty() || candidates.iter().any(|sig| sig.arity.accepts(argc)) {
+            continue;
+        }
+        let Some(channel) = send_channel(send) else {
+            continue;
+        };
+        let expected = candidates
+            .iter()
+            .map(|sig| sig.arity.to_string())
+            .collect::<Vec<_>>()
+            .join(" or ");
+        diagnostics.push(Diagnostic {
+            range: node_range(channel),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("arity-mismatch".into())),
+            source: Some("rholang-lsp".into()),
+            message: format!(
+                "Contract `{}` expects {expected}, but this send passes {argc}",
+                syntax::text(channel, source)
+            ),
+            related_information: Some(
+                candidates
+                    .iter()
+                    .map(|sig| DiagnosticRelatedInformation {
+                        location: sig.location.clone(),
+                        message: format!("contract `{}` takes {}", sig.name, sig.arity),
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        });
+    }
+
+    for bind in &sites.binds {
+        let Some(source_node) = bind_source(*bind) else {
+            continue;
+        };
+        let Some(key @ Channel::Bound(_)) = scopes.channel(source_node) else {
+            continue;
+        };
+        let arity = Arity::of_names(bind.child_by_field_name("names"));
+        let on_channel: Vec<&(Channel, Node, usize)> =
+            sends.iter().filter(|(k, _, _)| *k == key).collect();
+        if on_channel.is_empty() || on_channel.iter().any(|(_, _, argc)| arity.accepts(*argc)) {
+            continue;
+        }
+        diagnostics.push(Diagnostic {
+            range: node_range(*bind),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("arity-mismatch".into())),
+            source: Some("rholang-lsp".into()),
+            message: format!(
+                "Receive on `{}` expects {arity}, but no send on it matches and it will never fire",
+                syntax::text(source_node, source)
+            ),
+            related_information: Some(
+                on_channel
+                    .iter()
+                    .map(|(_, send, argc)| DiagnosticRelatedInformation {
+                        location: Location {
+                            uri: uri.clone(),
+                            range: node_range(*send),
+                        },
+                        message: format!("send with {argc} argument(s)"),
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        });
+    }
+}
@@ -1,23 +1,46 @@
-use std::sync::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, RwLock};
 
 use dashmap::DashMap;
-use tower_lsp::jsonrpc::Result;
+use serde_json::Value;
+use tower_lsp::jsonrpc::{Error, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 use tree_sitter::Parser;
 
+use crate::arity::{self, ContractSig};
+use crate::code_actions;
+use crate::completion;
+use crate::config::Settings;
+use crate::convert;
+use crate::declare;
 use crate::definition;
 use crate::diagnostics;
 use crate::document::Document;
+use crate::extract;
+use crate::hoist;
 use crate::hover;
+use crate::inline;
+use crate::joins;
+use crate::registry;
 use crate::rename;
+use crate::returns;
+use crate::scope::Scopes;
 use crate::semantic_tokens::{self, LEGEND_TYPE};
+use crate::signature::{self, ChangeSignature};
 use crate::symbols;
+use crate::system;
+use crate::unused;
 
 pub struct Backend {
     client: Client,
     documents: DashMap<Url, Document>,
+    /// Contracts each open document declares on free names, refreshed on every change.
+    exports: DashMap<Url, Vec<ContractSig>>,
+    /// Well-formed `rho:id:` ids each open document uses, refreshed on every change.
+    registry_ids: DashMap<Url, Vec<String>>,
     parser: Mutex<Parser>,
+    settings: RwLock<Settings>,
 }
 
 impl Backend {
@@ -30,32 +53,213 @@ impl Backend {
         Backend {
             client,
             documents: DashMap::new(),
+            exports: DashMap::new(),
+            registry_ids: DashMap::new(),
             parser: Mutex::new(parser),
+            settings: RwLock::new(Settings::default()),
         }
     }
 
     async fn publish_diagnostics(&self, uri: &Url) {
+        let settings = self.settings.read().unwrap().clone();
+        let workspace = self.workspace_contracts(uri);
+        let registry_ids = self.workspace_registry_ids(uri);
         if let Some(doc) = self.documents.get(uri) {
-            let diags = diagnostics::collect_diagnostics(&doc);
+            let diags =
+                diagnostics::collect_diagnostics(&doc, uri, &settings, &workspace, &registry_ids);
             self.client
                 .publish_diagnostics(uri.clone(), diags, None)
                 .await;
         }
     }
+
+    /// Contracts on free names declared in every open document except `except`.
+    fn workspace_contracts(&self, except: &Url) -> Vec<ContractSig> {
+        self.exports
+            .iter()
+            .filter(|entry| entry.key() != except)
+            .flat_map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    /// Recompute the contracts `uri` exports (none once it is closed) and return the
+    /// names whose signatures were added, removed or changed.
+    fn update_exports(&self, uri: &Url) -> HashSet<String> {
+        let exported = match self.documents.get(uri) {
+            Some(doc) => {
+                let scopes = Scopes::analyze(&doc);
+                arity::exported_contracts(&doc, &scopes, uri)
+            }
+            None => Vec::new(),
+        };
+        let previous = if exported.is_empty() {
+            self.exports.remove(uri).map(|(_, sigs)| sigs)
+        } else {
+            self.exports.insert(uri.clone(), exported.clone())
+        }
+        .unwrap_or_default();
+
+        let signature = |sig: &ContractSig| (sig.name.clone(), sig.arity.count, sig.arity.variadic);
+        let before: HashSet<_> = previous.iter().map(signature).collect();
+        let after: HashSet<_> = exported.iter().map(signature).collect();
+        before
+            .symmetric_difference(&after)
+            .map(|(name, _, _)| name.clone())
+            .collect()
+    }
+
+    /// Recompute the registry ids `uri` uses (none once it is closed) and return the
+    /// ids it started or stopped using.
+    fn update_registry_ids(&self, uri: &Url) -> HashSet<String> {
+        let ids = match self.documents.get(uri) {
+            Some(doc) => registry::valid_ids(&doc),
+            None => Vec::new(),
+        };
+        let previous = if ids.is_empty() {
+            self.registry_ids.remove(uri).map(|(_, ids)| ids)
+        } else {
+            self.registry_ids.insert(uri.clone(), ids.clone())
+        }
+        .unwrap_or_default();
+
+        let before: HashSet<String> = previous.into_iter().collect();
+        let after: HashSet<String> = ids.into_iter().collect();
+        before.symmetric_difference(&after).cloned().collect()
+    }
+
+    /// Republish the other open documents sending on any of the `contracts` or
+    /// mentioning any of the registry `ids`, whose diagnostics depend on them.
+    async fn publish_dependents(
+        &self,
+        uri: &Url,
+        contracts: &HashSet<String>,
+        ids: &HashSet<String>,
+    ) {
+        if contracts.is_empty() && ids.is_empty() {
+            return;
+        }
+        let dependents: Vec<Url> = self
+            .documents
+            .iter()
+            .filter(|entry| {
+                entry.key() != uri
+                    && (!contracts.is_empty() && arity::sends_to_any(entry.value(), contracts)
+                        || registry::mentions_any(entry.value(), ids))
+            })
+            .map(|entry| entry.key().clone())
+            .collect();
+        for dependent in &dependents {
+            self.publish_diagnostics(dependent).await;
+        }
+    }
+
+    /// Edits giving a contract new formals and updating its sends in every open
+    /// document, in the same shape as `rename::rename` but across files.
+    fn change_signature(&self, change: &ChangeSignature) -> Result<WorkspaceEdit> {
+        let (plan, edits) = {
+            let doc = self
+                .documents
+                .get(&change.uri)
+                .ok_or_else(|| Error::invalid_params("document is not open"))?;
+            signature::plan(&doc, change).map_err(Error::invalid_params)?
+        };
+        let mut changes = HashMap::new();
+        changes.insert(change.uri.clone(), edits);
+        for entry in self.documents.iter().filter(|e| e.key() != &change.uri) {
+            let edits = signature::call_site_edits(entry.value(), &plan);
+            if !edits.is_empty() {
+                changes.insert(entry.key().clone(), edits);
+            }
+        }
+        Ok(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        })
+    }
+
+    /// Refactoring adding a return channel to the contract at `range`, with the callers
+    /// in every open document waiting for it.
+    fn add_return_channel(&self, uri: &Url, range: Range) -> Option<CodeActionOrCommand> {
+        let (plan, edits) = {
+            let doc = self.documents.get(uri)?;
+            returns::plan(&doc, range)?
+        };
+        let mut changes = HashMap::new();
+        changes.insert(uri.clone(), edits);
+        for entry in self.documents.iter().filter(|e| e.key() != uri) {
+            let edits = returns::call_site_edits(entry.value(), &plan)?;
+            if !edits.is_empty() {
+                changes.insert(entry.key().clone(), edits);
+            }
+        }
+        let edit = WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        };
+        Some(code_actions::edit_action(
+            plan.title(),
+            CodeActionKind::REFACTOR_REWRITE,
+            edit,
+        ))
+    }
+
+    /// Well-formed `rho:id:` ids used in every open document except `except`.
+    fn workspace_registry_ids(&self, except: &Url) -> Vec<String> {
+        self.registry_ids
+            .iter()
+            .filter(|entry| entry.key() != except)
+            .flat_map(|entry| entry.value().clone())
+            .collect()
+    }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        if let Some(options) = &params.initialization_options {
+            *self.settings.write().unwrap() = Settings::from_json(options);
+        }
+        // Positions are byte columns throughout; clients that can count in UTF-8 agree
+        let utf8 = params
+            .capabilities
+            .general
+            .as_ref()
+            .and_then(|g| g.position_encodings.as_ref())
+            .is_some_and(|e| e.contains(&PositionEncodingKind::UTF8));
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
+                position_encoding: utf8.then_some(PositionEncodingKind::UTF8),
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
                     TextDocumentSyncKind::FULL,
                 )),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec![".".into()]),
+                    ..Default::default()
+                }),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".into(), ",".into()]),
+                    ..Default::default()
+                }),
                 definition_provider: Some(OneOf::Left(true)),
                 references_provider: Some(OneOf::Left(true)),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::REFACTOR_EXTRACT,
+                            CodeActionKind::REFACTOR_INLINE,
+                            CodeActionKind::REFACTOR_REWRITE,
+                            unused::REMOVE_UNUSED,
+                        ]),
+                        ..Default::default()
+                    },
+                )),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![signature::CHANGE_SIGNATURE.into()],
+                    ..Default::default()
+                }),
                 rename_provider: Some(OneOf::Right(RenameOptions {
                     prepare_provider: Some(true),
                     work_done_progress_options: WorkDoneProgressOptions::default(),
@@ -90,6 +294,14 @@ impl LanguageServer for Backend {
         Ok(())
     }
 
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        *self.settings.write().unwrap() = Settings::from_json(&params.settings);
+        let uris: Vec<Url> = self.documents.iter().map(|d| d.key().clone()).collect();
+        for uri in &uris {
+            self.publish_diagnostics(uri).await;
+        }
+    }
+
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let uri = params.text_document.uri.clone();
         let source = params.text_document.text;
@@ -99,7 +311,10 @@ impl LanguageServer for Backend {
                 self.documents.insert(uri.clone(), doc);
             }
         }
+        let contracts = self.update_exports(&uri);
+        let ids = self.update_registry_ids(&uri);
         self.publish_diagnostics(&uri).await;
+        self.publish_dependents(&uri, &contracts, &ids).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
@@ -113,14 +328,22 @@ impl LanguageServer for Backend {
                 self.documents.insert(uri.clone(), doc);
             }
         }
+        let contracts = self.update_exports(&uri);
+        let ids = self.update_registry_ids(&uri);
         self.publish_diagnostics(&uri).await;
+        self.publish_dependents(&uri, &contracts, &ids).await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri;
         self.documents.remove(&uri);
+        let contracts = self.update_exports(&uri);
+        let ids = self.update_registry_ids(&uri);
         // Clear diagnostics
-        self.client.publish_diagnostics(uri, vec![], None).await;
+        self.client
+            .publish_diagnostics(uri.clone(), vec![], None)
+            .await;
+        self.publish_dependents(&uri, &contracts, &ids).await;
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
@@ -129,6 +352,24 @@ impl LanguageServer for Backend {
         Ok(self.documents.get(uri).and_then(|doc| hover::hover(&doc, pos)))
     }
 
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let pos = params.text_document_position.position;
+        Ok(self
+            .documents
+            .get(uri)
+            .and_then(|doc| completion::completion(&doc, pos)))
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let pos = params.text_document_position_params.position;
+        Ok(self
+            .documents
+            .get(uri)
+            .and_then(|doc| system::signature_help(&doc, pos)))
+    }
+
     async fn goto_definition(
         &self,
         params: GotoDefinitionParams,
@@ -161,6 +402,34 @@ impl LanguageServer for Backend {
         }))
     }
 
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+        let mut actions = code_actions::quick_fixes(uri, &params.context.diagnostics);
+        if let Some(doc) = self.documents.get(uri) {
+            actions.extend(declare::actions(&doc, uri, params.range));
+            actions.extend(unused::remove_all(&doc, uri));
+            actions.extend(extract::actions(&doc, uri, params.range));
+            actions.extend(inline::actions(&doc, uri, params.range));
+            actions.extend(convert::actions(&doc, uri, params.range));
+            actions.extend(hoist::actions(&doc, uri, params.range));
+            actions.extend(joins::actions(&doc, uri, params.range));
+        }
+        actions.extend(self.add_return_channel(uri, params.range));
+        Ok(Some(actions).filter(|a| !a.is_empty()))
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
+        if params.command != signature::CHANGE_SIGNATURE {
+            return Ok(None);
+        }
+        let Some(change) = params.arguments.first().and_then(ChangeSignature::from_json) else {
+            return Err(Error::invalid_params("expected { uri, position, formals }"));
+        };
+        let edit = self.change_signature(&change)?;
+        let _ = self.client.apply_edit(edit.clone()).await;
+        Ok(serde_json::to_value(edit).ok())
+    }
+
     async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
         let uri = &params.text_document_position.text_document.uri;
         let pos = params.text_document_position.position;
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::flow::{self, Facts, Sites};
+use crate::scope::Scopes;
+use crate::syntax::{self, binary_parts, bind_source, is_bind, send_channel, unwrap_group};
+
+/// What a bundled name may be used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Capability {
+    read: bool,
+    write: bool,
+}
+
+impl Capability {
+    fn describe(self) -> &'static str {
+        match (self.read, self.write) {
+            (true, false) => "read-only bundle (`bundle-`)",
+            (false, true) => "write-only bundle (`bundle+`)",
+            (false, false) => "equality-only bundle (`bundle0`)",
+            (true, true) => "bundle",
+        }
+    }
+}
+
+fn bundle_capability(bundle: Node) -> Option<Capability> {
+    let mut cursor = bundle.walk();
+    let kind = bundle.children(&mut cursor).find_map(|c| match c.kind() {
+        "bundle_write" => Some((false, true)),
+        "bundle_read" => Some((true, false)),
+        "bundle_equiv" => Some((false, false)),
+        "bundle_read_write" => Some((true, true)),
+        _ => None,
+    });
+    kind.map(|(read, write)| Capability { read, write })
+}
+
+struct Analysis<'s, 'a> {
+    scopes: &'s Scopes<'a>,
+    /// Capability of each binder known to hold a bundle, by binder index.
+    caps: HashMap<usize, Capability>,
+}
+
+impl Analysis<'_, '_> {
+    /// The capability of a name or process expression, if it is known to be a bundle.
+    fn capability(&self, node: Node) -> Option<Capability> {
+        let node = unwrap_group(node);
+        match node.kind() {
+            "bundle" => bundle_capability(node),
+            "var" => self
+                .scopes
+                .binder_of(node)
+                .and_then(|b| self.caps.get(&b).copied()),
+            "quote" | "eval" => match syntax::named_children(node)[..] {
+                [inner] => self.capability(inner),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Facts<Capability> for Analysis<'_, '_> {
+    fn value_of(&self, node: Node) -> Option<Capability> {
+        self.capability(node)
+    }
+
+    fn set(&mut self, var: Node, cap: Capability) -> bool {
+        match self.scopes.binder_of(var) {
+            Some(b) => self.caps.insert(b, cap) != Some(cap),
+            None => false,
+        }
+    }
+}
+
+/// Track names wrapped in `bundle+`, `bundle-` and `bundle0` through `let` and
+/// receive bindings, and report uses their capability forbids.
+pub fn check(doc: &Document, scopes: &Scopes, diagnostics: &mut Vec<Diagnostic>) {
+    let root = doc.tree.root_node();
+    let sites = Sites::collect(root, scopes);
+    let mut analysis = Analysis {
+        scopes,
+        caps: HashMap::new(),
+    };
+    flow::propagate(&sites, scopes, &mut analysis);
+
+    let source = doc.source.as_str();
+    let mut report = |node: Node, message: String| {
+        diagnostics.push(Diagnostic {
+            range: node_range(node),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String("bundle-capability".into())),
+            source: Some("rholang-lsp".into()),
+            message,
+            ..Default::default()
+        });
+    };
+
+    for send in &sites.sends {
+        let Some(channel) = send_channel(*send) else {
+            continue;
+        };
+        if let Some(cap) = analysis.capability(channel).filter(|c| !c.write) {
+            report(
+                channel,
+                format!(
+                    "Cannot send on `{}`: it is a {}",
+                    syntax::text(channel, source),
+                    cap.describe()
+                ),
+            );
+        }
+    }
+
+    for bind in collect_receive_channels(root) {
+        if let Some(cap) = analysis.capability(bind).filter(|c| !c.read) {
+            report(
+                bind,
+                format!(
+                    "Cannot receive on `{}`: it is a {}",
+                    syntax::text(bind, source),
+                    cap.describe()
+                ),
+            );
+        }
+    }
+
+    // A `bundle0` name can only be compared or handed on; anything else is a mistake.
+    for eval in collect_evals(root) {
+        let Some(cap) = analysis.capability(eval).filter(|c| !c.read && !c.write) else {
+            continue;
+        };
+        if is_compared_or_sent(eval) {
+            continue;
+        }
+        report(
+            eval,
+            format!(
+                "`{}` is an {} and can only be compared with `==` or `!=`",
+                syntax::text(eval, source),
+                cap.describe()
+            ),
+        );
+    }
+}
+
+/// Dereferences and method receivers, checked for `bundle0` misuse.
+fn collect_evals(node: Node) -> Vec<Node> {
+    let mut out = Vec::new();
+    if node.kind() == "eval" {
+        out.push(node);
+    } else if node.kind() == "method" {
+        out.extend(node.child_by_field_name("receiver"));
+    }
+    for child in syntax::named_children(node) {
+        out.extend(collect_evals(child));
+    }
+    out
+}
+
+/// The channels of every bind and the names of every contract.
+fn collect_receive_channels(node: Node) -> Vec<Node> {
+    let mut out = Vec::new();
+    if is_bind(node) {
+        out.extend(bind_source(node));
+    } else if node.kind() == "contract" {
+        out.extend(node.child_by_field_name("name"));
+    }
+    for child in syntax::named_children(node) {
+        out.extend(collect_receive_channels(child));
+    }
+    out
+}
+
+/// Whether an expression is an operand of `==`/`!=` or an argument of a send.
+fn is_compared_or_sent(node: Node) -> bool {
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            "block" | "parenthesized" => current = parent,
+            "inputs" => return true,
+            _ => {
+                return matches!(binary_parts(parent), Some((_, "==" | "!=", _)));
+            }
+        }
+    }
+    false
+}
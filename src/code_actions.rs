@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::document::Document;
+
+/// Attach a quick fix to a diagnostic. The edits travel in the diagnostic's `data`
+/// and come back with the `codeAction` request, so fixes need no re-analysis.
+pub fn attach_fix(diagnostic: &mut Diagnostic, title: impl Into<String>, edits: Vec<TextEdit>) {
+    let fix = json!({ "title": title.into(), "edits": edits });
+    match diagnostic
+        .data
+        .as_mut()
+        .and_then(|d| d.get_mut("fixes"))
+        .and_then(Value::as_array_mut)
+    {
+        Some(fixes) => fixes.push(fix),
+        None => diagnostic.data = Some(json!({ "fixes": [fix] })),
+    }
+}
+
+/// Quick fixes for the diagnostics the client sent back with a `codeAction` request.
+pub fn quick_fixes(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+    for diagnostic in diagnostics {
+        let Some(fixes) = diagnostic
+            .data
+            .as_ref()
+            .and_then(|d| d.get("fixes"))
+            .and_then(Value::as_array)
+        else {
+            continue;
+        };
+        for fix in fixes {
+            let Some(title) = fix.get("title").and_then(Value::as_str) else {
+                continue;
+            };
+            let Ok(edits) = serde_json::from_value::<Vec<TextEdit>>(fix["edits"].clone()) else {
+                continue;
+            };
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: title.to_string(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(single_file_edit(uri, edits)),
+                is_preferred: Some(fixes.len() == 1),
+                ..Default::default()
+            }));
+        }
+    }
+    actions
+}
+
+pub fn single_file_edit(uri: &Url, edits: Vec<TextEdit>) -> WorkspaceEdit {
+    let mut changes = HashMap::new();
+    changes.insert(uri.clone(), edits);
+    WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    }
+}
+
+/// The edit replacing a byte range of the document with `new_text`.
+pub fn span_edit(doc: &Document, span: std::ops::Range<usize>, new_text: String) -> TextEdit {
+    let (start_line, start_col) = doc.position_at(span.start);
+    let (end_line, end_col) = doc.position_at(span.end);
+    TextEdit {
+        range: Range {
+            start: Position::new(start_line as u32, start_col as u32),
+            end: Position::new(end_line as u32, end_col as u32),
+        },
+        new_text,
+    }
+}
+
+/// A code action applying `edit`, for refactorings offered at the cursor rather than
+/// attached to a diagnostic.
+pub fn edit_action(
+    title: impl Into<String>,
+    kind: CodeActionKind,
+    edit: WorkspaceEdit,
+) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.into(),
+        kind: Some(kind),
+        edit: Some(edit),
+        ..Default::default()
+    })
+}
+
+/// Edits deleting the flagged entries of a comma-separated list together with their
+/// separators. Entries after the first kept one take the separator before them, the
+/// others the one after, so the edits never overlap.
+pub fn remove_items(items: &[Node], remove: &[bool]) -> Vec<TextEdit> {
+    let first_kept = remove.iter().position(|r| !r);
+    let mut edits = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        if !remove[i] {
+            continue;
+        }
+        let range = match first_kept {
+            Some(kept) if i > kept => Range {
+                start: node_range(items[i - 1]).end,
+                end: node_range(*item).end,
+            },
+            _ => Range {
+                start: node_range(*item).start,
+                end: items
+                    .get(i + 1)
+                    .map_or(node_range(*item).end, |next| node_range(*next).start),
+            },
+        };
+        edits.push(TextEdit {
+            range,
+            new_text: String::new(),
+        });
+    }
+    edits
+}
+
+/// Edits replacing `outer` (a `new`, `let`, ...) by its `body`: the header up to the
+/// body's contents and the trailer after them are deleted, so edits inside the body
+/// stay valid.
+pub fn unwrap_edits(outer: Node, body: Node) -> Vec<TextEdit> {
+    let (first, last) = if body.kind() == "block" && body.child_count() > 2 {
+        (body.child(1), body.child(body.child_count() - 2))
+    } else {
+        (Some(body), Some(body))
+    };
+    let (Some(first), Some(last)) = (first, last) else {
+        return Vec::new();
+    };
+    let delete = |start: Position, end: Position| TextEdit {
+        range: Range { start, end },
+        new_text: String::new(),
+    };
+    vec![
+        delete(node_range(outer).start, node_range(first).start),
+        delete(node_range(last).end, node_range(outer).end),
+    ]
+}
+
+/// Re-indent `text` taken from `column` so its lines start with `indent`. The first
+/// line is assumed to start at `column` already; continuation lines lose up to
+/// `column` leading spaces.
+pub fn reindent(text: &str, column: usize, indent: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = if i == 0 {
+                line
+            } else {
+                let spaces = line.len() - line.trim_start_matches(' ').len();
+                &line[spaces.min(column)..]
+            };
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{indent}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
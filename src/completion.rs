@@ -0,0 +1,68 @@
+use tower_lsp::lsp_types::*;
+
+use crate::document::Document;
+use crate::methods::{Method, METHODS};
+use crate::scope::Scopes;
+use crate::types::{Inference, Type};
+
+/// Completion at the given position: built-in methods after a `.`.
+pub fn completion(doc: &Document, pos: Position) -> Option<CompletionResponse> {
+    let offset = doc.offset_at(pos.line as usize, pos.character as usize);
+    let before = doc.source.get(..offset)?;
+    let word_start = before
+        .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_')
+        .len();
+    if !before[..word_start].ends_with('.') {
+        return None;
+    }
+
+    let receiver = receiver_type(doc, word_start - 1);
+    let items = METHODS
+        .iter()
+        .filter(|m| receiver.is_none_or(|ty| m.accepts(ty)))
+        .map(method_item)
+        .collect();
+    Some(CompletionResponse::Array(items))
+}
+
+/// The inferred type of the expression ending right before the `.` at `dot`.
+fn receiver_type(doc: &Document, dot: usize) -> Option<Type> {
+    let end = doc.source[..dot].trim_end().len();
+    if end == 0 {
+        return None;
+    }
+    let mut node = doc
+        .tree
+        .root_node()
+        .named_descendant_for_byte_range(end - 1, end)?;
+    while let Some(parent) = node.parent() {
+        if parent.end_byte() != end || parent.kind() == "method" {
+            break;
+        }
+        node = parent;
+    }
+    let scopes = Scopes::analyze(doc);
+    Inference::new(doc, &scopes).type_of(node)
+}
+
+fn method_item(method: &Method) -> CompletionItem {
+    let snippet = method
+        .params
+        .iter()
+        .enumerate()
+        .map(|(i, p)| format!("${{{}:{p}}}", i + 1))
+        .collect::<Vec<_>>()
+        .join(", ");
+    CompletionItem {
+        label: method.name.to_string(),
+        kind: Some(CompletionItemKind::METHOD),
+        detail: Some(method.signature()),
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: method.markdown(),
+        })),
+        insert_text: Some(format!("{}({snippet})", method.name)),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        ..Default::default()
+    }
+}
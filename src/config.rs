@@ -0,0 +1,26 @@
+use serde_json::Value;
+
+/// User-configurable server settings.
+///
+/// Read from `initializationOptions` and `workspace/didChangeConfiguration`, either
+/// directly or nested under `rholang.lsp` (the VSCode configuration section).
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    /// Warn when a binder hides an outer binder of the same name.
+    pub shadowing: bool,
+}
+
+impl Settings {
+    pub fn from_json(value: &Value) -> Self {
+        let value = value.pointer("/rholang/lsp").unwrap_or(value);
+        let flag = |path: &str, default: bool| {
+            value
+                .pointer(path)
+                .and_then(Value::as_bool)
+                .unwrap_or(default)
+        };
+        Settings {
+            shadowing: flag("/diagnostics/shadowing", false),
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::scope::Scopes;
+use crate::syntax::{self, binary_parts, named_children, unwrap_group};
+
+/// The value of a constant expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Int(i64),
+    Bool(bool),
+    String(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{n}"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::String(s) => write!(f, "\"{s}\""),
+        }
+    }
+}
+
+/// Magnitude of `i64::MIN`, only valid directly under a unary minus.
+const MIN_MAGNITUDE: &str = "9223372036854775808";
+
+/// Evaluates constant expressions, following `let`-bound constants.
+pub struct Folder<'s, 'a> {
+    scopes: &'s Scopes<'a>,
+    source: &'a str,
+}
+
+impl<'s, 'a> Folder<'s, 'a> {
+    pub fn new(doc: &'a Document, scopes: &'s Scopes<'a>) -> Self {
+        Folder {
+            scopes,
+            source: &doc.source,
+        }
+    }
+
+    /// The value of `node`, if it is a constant expression that evaluates without error.
+    pub fn eval(&self, node: Node) -> Option<Value> {
+        self.eval_depth(node, 0)
+    }
+
+    fn eval_depth(&self, node: Node, depth: usize) -> Option<Value> {
+        // `let` chains could be cyclic in broken code
+        if depth > 32 {
+            return None;
+        }
+        let eval = |n: Node| self.eval_depth(n, depth + 1);
+        let node = unwrap_group(node);
+        let text = syntax::text(node, self.source);
+        match node.kind() {
+            "long_literal" => return text.parse().ok().map(Value::Int),
+            "bool_literal" => return Some(Value::Bool(text == "true")),
+            "string_literal" => {
+                return Some(Value::String(
+                    text.strip_prefix('"')?.strip_suffix('"')?.to_string(),
+                ))
+            }
+            "neg" => {
+                let operand = unwrap_group(named_children(node).into_iter().next()?);
+                if operand.kind() == "long_literal"
+                    && syntax::text(operand, self.source) == MIN_MAGNITUDE
+                {
+                    return Some(Value::Int(i64::MIN));
+                }
+                return match eval(operand)? {
+                    Value::Int(n) => n.checked_neg().map(Value::Int),
+                    _ => None,
+                };
+            }
+            "not" => {
+                return match eval(named_children(node).into_iter().next()?)? {
+                    Value::Bool(b) => Some(Value::Bool(!b)),
+                    _ => None,
+                }
+            }
+            "var" => return eval(self.scopes.let_value(node)?),
+            "eval" => {
+                return eval(
+                    self.scopes
+                        .let_value(named_children(node).into_iter().next()?)?,
+                )
+            }
+            _ => {}
+        }
+
+        let (left, op, right) = binary_parts(node)?;
+        let (l, r) = (eval(left)?, eval(right)?);
+        Some(match (l, r) {
+            (Value::Int(a), Value::Int(b)) => match op {
+                "+" => Value::Int(a.checked_add(b)?),
+                "-" => Value::Int(a.checked_sub(b)?),
+                "*" => Value::Int(a.checked_mul(b)?),
+                "/" => Value::Int(a.checked_div(b)?),
+                "%" => Value::Int(a.checked_rem(b)?),
+                "<" => Value::Bool(a < b),
+                "<=" => Value::Bool(a <= b),
+                ">" => Value::Bool(a > b),
+                ">=" => Value::Bool(a >= b),
+                "==" => Value::Bool(a == b),
+                "!=" => Value::Bool(a != b),
+                _ => return None,
+            },
+            (Value::Bool(a), Value::Bool(b)) => match op {
+                "and" => Value::Bool(a && b),
+                "or" => Value::Bool(a || b),
+                "==" => Value::Bool(a == b),
+                "!=" => Value::Bool(a != b),
+                _ => return None,
+            },
+            (Value::String(a), Value::String(b)) => match op {
+                "++" => Value::String(a + &b),
+                "==" => Value::Bool(a == b),
+                "!=" => Value::Bool(a != b),
+                "<" => Value::Bool(a < b),
+                "<=" => Value::Bool(a <= b),
+                ">" => Value::Bool(a > b),
+                ">=" => Value::Bool(a >= b),
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Hover text for the largest constant expression containing `node`, unless it is a
+/// plain literal.
+pub fn hover_value(doc: &Document, scopes: &Scopes, node: Node) -> Option<String> {
+    let folder = Folder::new(doc, scopes);
+    let mut expr = node;
+    let mut value = folder.eval(node);
+    while let Some(parent) = expr.parent() {
+        match folder.eval(parent) {
+            Some(v) => {
+                expr = parent;
+                value = Some(v);
+            }
+            None => break,
+        }
+    }
+    if matches!(
+        unwrap_group(expr).kind(),
+        "long_literal" | "bool_literal" | "string_literal"
+    ) {
+        return None;
+    }
+    Some(format!("**value** `{}`", value?))
+}
+
+/// Report integer literals outside the 64-bit range, and division by zero and
+/// overflow in constant integer expressions.
+pub fn check(doc: &Document, scopes: &Scopes, diagnostics: &mut Vec<Diagnostic>) {
+    let folder = Folder::new(doc, scopes);
+    walk(doc.tree.root_node(), &doc.source, &folder, diagnostics);
+}
+
+fn walk(node: Node, source: &str, folder: &Folder, diagnostics: &mut Vec<Diagnostic>) {
+    if node.kind() == "long_literal" {
+        let text = syntax::text(node, source);
+        // `-(9223372036854775808)` folds to `i64::MIN` just like the bare form
+        let mut parent = node.parent();
+        while let Some(group) = parent.filter(|p| matches!(p.kind(), "block" | "parenthesized")) {
+            parent = group.parent();
+        }
+        let negated = parent.is_some_and(|p| p.kind() == "neg");
+        if text.parse::<i64>().is_err() && !(negated && text == MIN_MAGNITUDE) {
+            diagnostics.push(error(
+                node,
+                "literal-range",
+                format!("Integer literal `{text}` does not fit in 64 bits"),
+            ));
+        }
+    } else if node.kind() == "neg" {
+        let operand = named_children(node).into_iter().next();
+        if let Some(Value::Int(i64::MIN)) = operand.and_then(|o| folder.eval(o)) {
+            diagnostics.push(error(
+                node,
+                "constant-overflow",
+                "Negating the smallest 64-bit integer overflows".into(),
+            ));
+        }
+    } else if let Some((left, op, right)) = binary_parts(node) {
+        if let (Some(Value::Int(a)), Some(Value::Int(b))) = (folder.eval(left), folder.eval(right))
+        {
+            let result = match op {
+                "+" => Some(a.checked_add(b)),
+                "-" => Some(a.checked_sub(b)),
+                "*" => Some(a.checked_mul(b)),
+                "/" => Some(a.checked_div(b)),
+                "%" => Some(a.checked_rem(b)),
+                _ => None,
+            };
+            match result {
+                Some(None) if b == 0 => {
+                    let what = if op == "/" { "Division" } else { "Modulo" };
+                    diagnostics.push(error(right, "division-by-zero", format!("{what} by zero")));
+                }
+                Some(None) => diagnostics.push(error(
+                    node,
+                    "constant-overflow",
+                    format!("`{a} {op} {b}` overflows 64-bit integers"),
+                )),
+                _ => {}
+            }
+        }
+    }
+    for child in named_children(node) {
+        walk(child, source, folder, diagnostics);
+    }
+}
+
+fn error(node: Node, code: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        range: node_range(node),
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String(code.into())),
+        source: Some("rholang-lsp".into()),
+        message,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,206 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::{edit_action, single_file_edit, span_edit};
+use crate::document::Document;
+use crate::signature::paren_contents;
+use crate::syntax::{self, bind_source, is_bind, receipt_groups, send_operator};
+
+const BIND_ARROWS: &[(&str, &str)] = &[
+    ("<-", "linear receive"),
+    ("<=", "persistent receive"),
+    ("<<-", "peek"),
+];
+
+/// Rewrites at the cursor that keep a process's meaning or switch its persistence:
+/// a contract to the `for (... <= ...)` it abbreviates and back, the arrow of a
+/// receive and the operator of a send. Only the syntax around the names changes, so
+/// bodies keep their comments and layout.
+pub fn actions(doc: &Document, uri: &Url, range: Range) -> Vec<CodeActionOrCommand> {
+    let offset = doc.offset_at(range.start.line as usize, range.start.character as usize);
+    let Some(mut node) = doc
+        .tree
+        .root_node()
+        .named_descendant_for_byte_range(offset, offset)
+    else {
+        return Vec::new();
+    };
+    while !matches!(node.kind(), "contract" | "input" | "send") && !is_bind(node) {
+        let Some(parent) = node.parent() else {
+            return Vec::new();
+        };
+        node = parent;
+    }
+    // Within a contract or `for` body the refactoring belongs to some inner process
+    let in_body = node
+        .child_by_field_name("proc")
+        .is_some_and(|p| offset >= p.start_byte());
+    if in_body {
+        return Vec::new();
+    }
+
+    let mut rewrites: Vec<(String, Vec<TextEdit>)> = Vec::new();
+    match node.kind() {
+        "contract" => rewrites.extend(contract_to_for(doc, node)),
+        "input" => rewrites.extend(for_to_contract(doc, node)),
+        "send" => rewrites.extend(toggle_send(doc, node)),
+        _ => {
+            rewrites.extend(change_arrow(doc, node));
+            let input = ancestor(node, "input");
+            rewrites.extend(input.and_then(|i| for_to_contract(doc, i)));
+        }
+    }
+    rewrites
+        .into_iter()
+        .map(|(title, edits)| {
+            edit_action(
+                title,
+                CodeActionKind::REFACTOR_REWRITE,
+                single_file_edit(uri, edits),
+            )
+        })
+        .collect()
+}
+
+fn ancestor<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut node = node.parent()?;
+    while node.kind() != kind {
+        node = node.parent()?;
+    }
+    Some(node)
+}
+
+/// `contract c(x, y) = { P }` becomes `for (x, y <= c) { P }`.
+fn contract_to_for(doc: &Document, contract: Node) -> Option<(String, Vec<TextEdit>)> {
+    let source = &doc.source;
+    let name = contract.child_by_field_name("name")?;
+    let body = contract.child_by_field_name("proc")?;
+    let formals = paren_contents(contract, name.end_byte(), source)?;
+    let formals = source[formals].trim();
+    // `for` needs at least one pattern to receive into
+    if formals.is_empty() {
+        return None;
+    }
+    let header = format!("for ({formals} <= {}) ", syntax::text(name, source));
+    Some((
+        "Convert contract to persistent `for`".into(),
+        vec![span_edit(
+            doc,
+            contract.start_byte()..body.start_byte(),
+            header,
+        )],
+    ))
+}
+
+/// `for (x, y <= c) { P }` with a single persistent bind becomes
+/// `contract c(x, y) = { P }`.
+fn for_to_contract(doc: &Document, input: Node) -> Option<(String, Vec<TextEdit>)> {
+    let source = &doc.source;
+    let groups = input
+        .child_by_field_name("receipts")
+        .map(receipt_groups)
+        .unwrap_or_default();
+    let [group] = &groups[..] else {
+        return None;
+    };
+    let [bind] = group[..] else {
+        return None;
+    };
+    if bind.kind() != "repeated_bind" {
+        return None;
+    }
+    let channel = bind_source(bind).filter(|c| matches!(c.kind(), "var" | "quote"))?;
+    let names = bind
+        .child_by_field_name("names")
+        .map_or("", |n| syntax::text(n, source));
+    let body = input.child_by_field_name("proc")?;
+    if body.kind() != "block" {
+        return None;
+    }
+    let header = format!("contract {}({names}) = ", syntax::text(channel, source));
+    Some((
+        "Convert persistent `for` to contract".into(),
+        vec![span_edit(
+            doc,
+            input.start_byte()..body.start_byte(),
+            header,
+        )],
+    ))
+}
+
+/// Swap the arrow of a bind in a `for` for each of the other two. Every bind of a
+/// join must use the same arrow, so the whole `&` group changes together.
+fn change_arrow(doc: &Document, bind: Node) -> Vec<(String, Vec<TextEdit>)> {
+    // `select` branches only take linear binds
+    let Some(receipts) = ancestor(bind, "receipts") else {
+        return Vec::new();
+    };
+    let group = receipt_groups(receipts)
+        .into_iter()
+        .find(|g| g.iter().any(|b| b.id() == bind.id()))
+        .unwrap_or_else(|| vec![bind]);
+    let Some(current) = arrow_of(bind) else {
+        return Vec::new();
+    };
+    let subject = if group.len() > 1 { "join" } else { "receive" };
+    BIND_ARROWS
+        .iter()
+        .filter(|(a, _)| *a != current.kind())
+        .map(|(a, description)| {
+            let edits = group
+                .iter()
+                .filter_map(|b| arrow_of(*b))
+                .filter(|arrow| arrow.kind() != *a)
+                .map(|arrow| span_edit(doc, arrow.byte_range(), a.to_string()))
+                .collect();
+            (format!("Convert {subject} to {description} `{a}`"), edits)
+        })
+        .collect()
+}
+
+/// The `<-`, `<=` or `<<-` token of a bind.
+fn arrow_of(bind: Node) -> Option<Node> {
+    let mut cursor = bind.walk();
+    let arrow = bind
+        .children(&mut cursor)
+        .find(|c| !c.is_named() && BIND_ARROWS.iter().any(|(a, _)| *a == c.kind()));
+    arrow
+}
+
+/// Switch a send between `!` (consumed once) and `!!` (persistent).
+fn toggle_send(doc: &Document, send: Node) -> Option<(String, Vec<TextEdit>)> {
+    let (op, span) = send_operator(send, &doc.source)?;
+    let (replacement, title) = match op {
+        "!" => ("!!", "Convert to persistent send `!!`"),
+        "!!" => ("!", "Convert to single send `!`"),
+        _ => return None,
+    };
+    Some((title.into(), vec![span_edit(doc, span, replacement.into())]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{apply, parse, uri};
+
+    fn titled(actions: &[CodeActionOrCommand], title: &str) -> WorkspaceEdit {
+        actions
+            .iter()
+            .find_map(|a| match a {
+                CodeActionOrCommand::CodeAction(a) if a.title == title => a.edit.clone(),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("no action `{title}`"))
+    }
+
+    #[test]
+    fn join_arrows_change_together() {
+        let doc = parse("for (x <- a & y <- b) { Nil }");
+        let cursor = Range::new(Position::new(0, 5), Position::new(0, 5));
+        let actions = actions(&doc, &uri(), cursor);
+        let edit = titled(&actions, "Convert join to persistent receive `<=`");
+        assert_eq!(apply(&doc, &edit), "for (x <= a & y <= b) { Nil }");
+        let edit = titled(&actions, "Convert join to peek `<<-`");
+        assert_eq!(apply(&doc, &edit), "for (x <<- a & y <<- b) { Nil }");
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::{BTreeSet, HashMap};
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::orphans::{access, is_private, Access};
+use crate::scope::{Channel, Scopes};
+use crate::syntax::{bind_source, branch_binds, receipt_groups};
+
+/// A private, non-escaping channel with its receive and send sites.
+struct Tracked<'a> {
+    name: &'a str,
+    receives: Vec<Node<'a>>,
+    sends: Vec<Node<'a>>,
+}
+
+/// Private channels whose receives must fire before `node` can run: the channels of
+/// every enclosing `for`/`select` branch and every enclosing contract.
+fn guards(node: Node, scopes: &Scopes, tracked: &HashMap<usize, Tracked>) -> BTreeSet<usize> {
+    let mut guards = BTreeSet::new();
+    let mut child = node;
+    while let Some(parent) = child.parent() {
+        let in_body = parent
+            .child_by_field_name("proc")
+            .is_some_and(|p| p.id() == child.id());
+        if in_body {
+            let channels: Vec<Node> = match parent.kind() {
+                "input" => parent
+                    .child_by_field_name("receipts")
+                    .map(|r| {
+                        receipt_groups(r)
+                            .into_iter()
+                            .flatten()
+                            .filter_map(bind_source)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                "branch" => branch_binds(parent)
+                    .into_iter()
+                    .filter_map(bind_source)
+                    .collect(),
+                "contract" => parent.child_by_field_name("name").into_iter().collect(),
+                _ => Vec::new(),
+            };
+            for channel in channels {
+                if let Some(Channel::Bound(b)) = scopes.channel(channel) {
+                    if tracked.contains_key(&b) {
+                        guards.insert(b);
+                    }
+                }
+            }
+        }
+        child = parent;
+    }
+    guards
+}
+
+/// Report cycles of private channels where every send on each channel only happens
+/// after a receive on the next one, so none of the receives can ever fire.
+pub fn check(scopes: &Scopes, uri: &Url, diagnostics: &mut Vec<Diagnostic>) {
+    let mut tracked: HashMap<usize, Tracked> = HashMap::new();
+    for (index, binder) in scopes.binders.iter().enumerate() {
+        if !is_private(binder) {
+            continue;
+        }
+        let mut entry = Tracked {
+            name: binder.name,
+            receives: Vec::new(),
+            sends: Vec::new(),
+        };
+        let mut escapes = false;
+        for u in scopes.uses_of(index) {
+            match access(u.node) {
+                Access::Send => entry.sends.extend(u.node.parent()),
+                Access::Receive => entry.receives.push(u.node),
+                Access::Escape => escapes = true,
+            }
+        }
+        // Channels without both sides are the orphan check's business
+        if !escapes && !entry.receives.is_empty() && !entry.sends.is_empty() {
+            tracked.insert(index, entry);
+        }
+    }
+
+    // c -> d when every send on c is guarded by a receive on d
+    let mut edges: HashMap<usize, BTreeSet<usize>> = HashMap::new();
+    for (&c, entry) in &tracked {
+        let mut common: Option<BTreeSet<usize>> = None;
+        for send in &entry.sends {
+            let g = guards(*send, scopes, &tracked);
+            common = Some(match common {
+                Some(prev) => prev.intersection(&g).copied().collect(),
+                None => g,
+            });
+        }
+        edges.insert(c, common.unwrap_or_default());
+    }
+
+    let reach = |from: usize| -> BTreeSet<usize> {
+        let mut seen = BTreeSet::new();
+        let mut stack = vec![from];
+        while let Some(n) = stack.pop() {
+            for &next in edges.get(&n).into_iter().flatten() {
+                if seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        seen
+    };
+    let reachable: HashMap<usize, BTreeSet<usize>> =
+        tracked.keys().map(|&c| (c, reach(c))).collect();
+
+    let mut channels: Vec<usize> = tracked.keys().copied().collect();
+    channels.sort();
+    let mut reported = BTreeSet::new();
+    for &c in &channels {
+        if reported.contains(&c) || !reachable[&c].contains(&c) {
+            continue;
+        }
+        let cycle: Vec<usize> = channels
+            .iter()
+            .copied()
+            .filter(|d| reachable[&c].contains(d) && reachable[d].contains(&c))
+            .collect();
+        reported.extend(cycle.iter().copied());
+
+        let names: Vec<String> = cycle
+            .iter()
+            .map(|d| format!("`{}`", tracked[d].name))
+            .collect();
+        let waits: Vec<String> = cycle
+            .iter()
+            .map(|d| {
+                let on: Vec<String> = edges[d]
+                    .iter()
+                    .filter(|e| cycle.contains(e))
+                    .map(|e| format!("`{}`", tracked[e].name))
+                    .collect();
+                format!("sends on `{}` wait for {}", tracked[d].name, on.join(", "))
+            })
+            .collect();
+        let mut related = Vec::new();
+        for d in &cycle {
+            let receives = tracked[d].receives.iter().map(|n| ("receive on", n));
+            let sends = tracked[d].sends.iter().map(|n| ("send on", n));
+            for (what, node) in receives.chain(sends) {
+                related.push(DiagnosticRelatedInformation {
+                    location: Location {
+                        uri: uri.clone(),
+                        range: node_range(*node),
+                    },
+                    message: format!("{what} `{}`", tracked[d].name),
+                });
+            }
+        }
+        let Some(first) = cycle
+            .iter()
+            .flat_map(|d| tracked[d].receives.iter())
+            .min_by_key(|n| n.start_byte())
+        else {
+            continue;
+        };
+        diagnostics.push(Diagnostic {
+            range: node_range(*first),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("deadlock".into())),
+            source: Some("rholang-lsp".into()),
+            message: format!(
+                "Deadlock: receives on {} can never fire ({})",
+                names.join(", "),
+                waits.join("; ")
+            ),
+            related_information: Some(related),
+            ..Default::default()
+        });
+    }
+}
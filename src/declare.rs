@@ -0,0 +1,108 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::{edit_action, reindent, single_file_edit};
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::scope::{Scopes, Sort};
+use crate::sorts::expected_sort;
+use crate::syntax;
+use crate::system::catalog;
+
+/// Indentation added when wrapping a process in a new block.
+pub const INDENT: &str = "  ";
+
+/// Actions declaring the free name under the cursor: in the nearest enclosing `new`,
+/// or in a fresh `new` around the top-level process, optionally bound to the system
+/// channel of the same conventional name.
+pub fn actions(doc: &Document, uri: &Url, range: Range) -> Vec<CodeActionOrCommand> {
+    let offset = doc.offset_at(range.start.line as usize, range.start.character as usize);
+    let scopes = Scopes::analyze(doc);
+    let Some(var) = scopes
+        .uses
+        .iter()
+        .filter(|u| u.binder.is_none())
+        .map(|u| u.node)
+        .find(|n| n.start_byte() <= offset && offset <= n.end_byte())
+    else {
+        return Vec::new();
+    };
+    // Only names can be declared by `new`
+    let is_name = var
+        .parent()
+        .and_then(|p| expected_sort(var, p))
+        .is_none_or(|sort| sort == Sort::Name);
+    if !is_name {
+        return Vec::new();
+    }
+
+    let name = syntax::text(var, &doc.source);
+    let mut declarations = vec![(format!("Declare `{name}` with `new`"), name.to_string())];
+    for system in catalog().by_name(name) {
+        declarations.push((
+            format!("Declare `{name}` bound to `{}`", system.uri),
+            format!("{name}(`{}`)", system.uri),
+        ));
+    }
+
+    declarations
+        .into_iter()
+        .filter_map(|(title, decl)| {
+            let edit = match enclosing_new(var).and_then(|n| n.child_by_field_name("decls")) {
+                Some(decls) => TextEdit {
+                    range: Range {
+                        start: node_range(decls).end,
+                        end: node_range(decls).end,
+                    },
+                    new_text: format!(", {decl}"),
+                },
+                None => {
+                    let process = top_level(var)?;
+                    wrap_in_new(process, &decl, syntax::text(process, &doc.source))
+                }
+            };
+            Some(edit_action(
+                title,
+                CodeActionKind::QUICKFIX,
+                single_file_edit(uri, vec![edit]),
+            ))
+        })
+        .collect()
+}
+
+/// The innermost `new` whose body contains `node`.
+pub fn enclosing_new(node: Node) -> Option<Node> {
+    let mut child = node;
+    while let Some(parent) = child.parent() {
+        if parent.kind() == "new"
+            && parent
+                .child_by_field_name("proc")
+                .is_some_and(|p| p.id() == child.id())
+        {
+            return Some(parent);
+        }
+        child = parent;
+    }
+    None
+}
+
+/// The process directly under the source file that contains `node`.
+pub fn top_level(node: Node) -> Option<Node> {
+    let mut node = node;
+    while node.parent()?.kind() != "source_file" {
+        node = node.parent()?;
+    }
+    Some(node)
+}
+
+/// Replace `process` with `new <decl> in { <body> }`, where `body` is the new text of
+/// the process.
+pub fn wrap_in_new(process: Node, decl: &str, body: &str) -> TextEdit {
+    let column = process.start_position().column;
+    let outer = " ".repeat(column);
+    let body = reindent(body, column, &format!("{outer}{INDENT}"));
+    TextEdit {
+        range: node_range(process),
+        new_text: format!("new {decl} in {{\n{body}\n{outer}}}"),
+    }
+}
@@ -1,46 +1,103 @@
 use tower_lsp::lsp_types::*;
 use tree_sitter::Node;
 
+use crate::arity::{self, ContractSig};
+use crate::bundles;
+use crate::code_actions::attach_fix;
+use crate::config::Settings;
+use crate::constants;
+use crate::deadlock;
 use crate::document::Document;
+use crate::duplicates;
+use crate::exhaustiveness;
+use crate::interpolation;
+use crate::methods;
+use crate::orphans;
+use crate::patterns;
+use crate::registry;
+use crate::repair;
+use crate::scope::Scopes;
+use crate::shadowing;
+use crate::sorts;
+use crate::system;
+use crate::types::{self, Inference};
+use crate::unused;
 
-pub fn collect_diagnostics(doc: &Document) -> Vec<Diagnostic> {
+/// `workspace` holds the contracts exported by the other open documents and
+/// `registry_ids` the well-formed `rho:id:` ids they use.
+pub fn collect_diagnostics(
+    doc: &Document,
+    uri: &Url,
+    settings: &Settings,
+    workspace: &[ContractSig],
+    registry_ids: &[String],
+) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
-    collect_errors(doc.tree.root_node(), &doc.source, &mut diagnostics);
+    collect_errors(doc.tree.root_node(), doc, &mut diagnostics);
+
+    let scopes = Scopes::analyze(doc);
+    if settings.shadowing {
+        shadowing::check(&scopes, uri, &mut diagnostics);
+    }
+    arity::check(doc, &scopes, uri, workspace, &mut diagnostics);
+    bundles::check(doc, &scopes, &mut diagnostics);
+    sorts::check(&scopes, uri, &mut diagnostics);
+    patterns::check(doc, &scopes, &mut diagnostics);
+    exhaustiveness::check(doc, uri, &mut diagnostics);
+    duplicates::check(doc, uri, &mut diagnostics);
+    orphans::check(&scopes, uri, &mut diagnostics);
+    unused::check(&scopes, &mut diagnostics);
+    deadlock::check(&scopes, uri, &mut diagnostics);
+    let inference = Inference::new(doc, &scopes);
+    types::check(doc, &inference, &mut diagnostics);
+    constants::check(doc, &scopes, &mut diagnostics);
+    interpolation::check(doc, &scopes, &inference, &mut diagnostics);
+    methods::check(doc, &inference, &mut diagnostics);
+    system::check(doc, &scopes, &mut diagnostics);
+    registry::check(doc, registry_ids, &mut diagnostics);
     diagnostics
 }
 
-fn collect_errors(node: Node, source: &str, diagnostics: &mut Vec<Diagnostic>) {
+fn collect_errors(node: Node, doc: &Document, diagnostics: &mut Vec<Diagnostic>) {
     if node.is_error() {
         let range = node_range(node);
         let text = node
-            .utf8_text(source.as_bytes())
+            .utf8_text(doc.source.as_bytes())
             .unwrap_or("")
             .chars()
             .take(40)
             .collect::<String>();
-        diagnostics.push(Diagnostic {
+        let mut diagnostic = Diagnostic {
             range,
             severity: Some(DiagnosticSeverity::ERROR),
             source: Some("rholang-lsp".into()),
             message: format!("Syntax error near `{text}`"),
             ..Default::default()
-        });
+        };
+        for (title, edits) in repair::error_fixes(doc, node) {
+            attach_fix(&mut diagnostic, title, edits);
+        }
+        diagnostics.push(diagnostic);
     } else if node.is_missing() {
         let range = node_range(node);
         let kind = node.kind();
-        diagnostics.push(Diagnostic {
+        let mut diagnostic = Diagnostic {
             range,
             severity: Some(DiagnosticSeverity::ERROR),
             source: Some("rholang-lsp".into()),
             message: format!("Missing `{kind}`"),
             ..Default::default()
-        });
+        };
+        if let Some((title, edits)) = repair::missing_token(doc, node) {
+            attach_fix(&mut diagnostic, title, edits);
+        }
+        diagnostics.push(diagnostic);
     } else {
         // Only recurse into children if this node might contain errors
         if node.has_error() {
             let mut cursor = node.walk();
             for child in node.children(&mut cursor) {
-                collect_errors(child, source, diagnostics);
+                collect_errors(child, doc, diagnostics);
             }
         }
     }
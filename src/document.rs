@@ -64,18 +64,28 @@ impl Document {
         }
     }
 
-    fn offset_at(&self, line: usize, col: usize) -> usize {
+    /// Byte offset of a line/column position. Columns are byte offsets, as in
+    /// tree-sitter and in every position the server sends; a column inside a
+    /// multi-byte character falls back to its start, and one past the end of the line
+    /// (or its `\r\n`) is clamped to the line's end.
+    pub fn offset_at(&self, line: usize, col: usize) -> usize {
         let mut offset = 0;
-        for (i, l) in self.source.lines().enumerate() {
+        for (i, l) in self.source.split_inclusive('\n').enumerate() {
             if i == line {
-                return offset + col.min(l.len());
+                let text = l.trim_end_matches(['\n', '\r']);
+                let mut col = col.min(text.len());
+                while !text.is_char_boundary(col) {
+                    col -= 1;
+                }
+                return offset + col;
             }
-            offset += l.len() + 1; // +1 for newline
+            offset += l.len();
         }
         self.source.len()
     }
 
-    fn position_at(&self, byte_offset: usize) -> (usize, usize) {
+    /// Line and byte column of a byte offset.
+    pub fn position_at(&self, byte_offset: usize) -> (usize, usize) {
         let mut line = 0;
         let mut col = 0;
         for (i, ch) in self.source.char_indices() {
@@ -92,3 +102,25 @@ impl Document {
         (line, col)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::parse;
+
+    #[test]
+    fn positions_round_trip_on_multi_byte_lines() {
+        let doc = parse("new x in {\r\n  x!(\"héllo 😀\") | x!(1)\r\n}\r\n");
+        for offset in (0..doc.source.len()).filter(|&o| doc.source.is_char_boundary(o)) {
+            let (line, col) = doc.position_at(offset);
+            if doc.source[offset..].starts_with(['\r', '\n']) {
+                continue;
+            }
+            assert_eq!(doc.offset_at(line, col), offset, "offset {offset}");
+        }
+        // A column inside `é` or past the line end stays on a boundary
+        let inside = doc.source.find('é').unwrap() + 1;
+        let (line, col) = doc.position_at(inside - 1);
+        assert_eq!(doc.offset_at(line, col + 1), inside - 1);
+        assert_eq!(doc.offset_at(0, 100), "new x in {".len());
+    }
+}
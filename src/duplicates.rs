@@ -0,0 +1,157 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::{attach_fix, remove_items};
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::syntax::{self, named_children, unwrap_group};
+
+/// A canonical form of a ground process, equal for processes RNode considers equal
+/// (`01` and `1`, `Set(1, 2)` and `Set(2, 1)`). `None` when it is not ground.
+pub fn ground_key(node: Node, source: &str) -> Option<String> {
+    let node = unwrap_group(node);
+    let text = syntax::text(node, source);
+    let parts = |node: Node| -> Option<Vec<String>> {
+        named_children(node)
+            .into_iter()
+            .map(|c| ground_key(c, source))
+            .collect()
+    };
+    Some(match node.kind() {
+        "long_literal" => text.parse::<i64>().ok()?.to_string(),
+        "neg" => {
+            let inner = named_children(node).into_iter().next()?;
+            if unwrap_group(inner).kind() != "long_literal" {
+                return None;
+            }
+            format!("-{}", ground_key(inner, source)?)
+        }
+        "string_literal" | "bool_literal" | "uri_literal" | "nil" => text.to_string(),
+        "list" => format!("[{}]", parts(node)?.join(",")),
+        "tuple" => format!("({})", parts(node)?.join(",")),
+        "set" | "map" => {
+            let mut parts = parts(node)?;
+            parts.sort();
+            parts.dedup();
+            format!("{}({})", node.kind(), parts.join(","))
+        }
+        "key_value_pair" => format!(
+            "{}:{}",
+            ground_key(node.child_by_field_name("key")?, source)?,
+            ground_key(node.child_by_field_name("value")?, source)?
+        ),
+        _ => return None,
+    })
+}
+
+/// Warn about map keys given twice (only the last entry survives) and repeated set elements.
+pub fn check(doc: &Document, uri: &Url, diagnostics: &mut Vec<Diagnostic>) {
+    walk(doc.tree.root_node(), &doc.source, uri, diagnostics);
+}
+
+fn walk(node: Node, source: &str, uri: &Url, diagnostics: &mut Vec<Diagnostic>) {
+    match node.kind() {
+        "map" => {
+            let entries: Vec<Node> = named_children(node)
+                .into_iter()
+                .filter(|n| n.kind() == "key_value_pair")
+                .collect();
+            let keys: Vec<Option<String>> = entries
+                .iter()
+                .map(|e| ground_key(e.child_by_field_name("key")?, source))
+                .collect();
+            for (i, key) in keys.iter().enumerate() {
+                let Some(key) = key else { continue };
+                // The earlier entry is the redundant one: the last value wins
+                let Some(later) = (i + 1..entries.len()).find(|&j| keys[j].as_ref() == Some(key))
+                else {
+                    continue;
+                };
+                let key_node = entries[i].child_by_field_name("key").unwrap_or(entries[i]);
+                let mut diagnostic = duplicate(
+                    entries[i],
+                    "duplicate-key",
+                    format!(
+                        "Key `{}` is overwritten by a later entry",
+                        syntax::text(key_node, source)
+                    ),
+                    uri,
+                    entries[later],
+                    "overwritten by",
+                );
+                attach_fix(
+                    &mut diagnostic,
+                    "Remove overwritten entry",
+                    remove_items(&entries, &flag(entries.len(), i)),
+                );
+                diagnostics.push(diagnostic);
+            }
+        }
+        "set" => {
+            let elements: Vec<Node> = named_children(node)
+                .into_iter()
+                .filter(|n| !n.kind().contains("remainder"))
+                .collect();
+            let keys: Vec<Option<String>> =
+                elements.iter().map(|e| ground_key(*e, source)).collect();
+            for (i, key) in keys.iter().enumerate() {
+                let Some(key) = key else { continue };
+                let Some(first) = (0..i).find(|&j| keys[j].as_ref() == Some(key)) else {
+                    continue;
+                };
+                let mut diagnostic = duplicate(
+                    elements[i],
+                    "duplicate-element",
+                    format!(
+                        "Element `{}` already occurs in this set",
+                        syntax::text(elements[i], source)
+                    ),
+                    uri,
+                    elements[first],
+                    "first occurrence",
+                );
+                attach_fix(
+                    &mut diagnostic,
+                    "Remove duplicate element",
+                    remove_items(&elements, &flag(elements.len(), i)),
+                );
+                diagnostics.push(diagnostic);
+            }
+        }
+        _ => {}
+    }
+    for child in named_children(node) {
+        walk(child, source, uri, diagnostics);
+    }
+}
+
+fn duplicate(
+    node: Node,
+    code: &str,
+    message: String,
+    uri: &Url,
+    other: Node,
+    other_message: &str,
+) -> Diagnostic {
+    Diagnostic {
+        range: node_range(node),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(code.into())),
+        source: Some("rholang-lsp".into()),
+        message,
+        tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+        related_information: Some(vec![DiagnosticRelatedInformation {
+            location: Location {
+                uri: uri.clone(),
+                range: node_range(other),
+            },
+            message: other_message.into(),
+        }]),
+        ..Default::default()
+    }
+}
+
+/// Removal flags selecting only `index`.
+fn flag(len: usize, index: usize) -> Vec<bool> {
+    (0..len).map(|i| i == index).collect()
+}
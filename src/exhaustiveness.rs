@@ -0,0 +1,152 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::syntax::{self, named_children, unwrap_group};
+
+/// How much of the value space a case pattern covers.
+#[derive(Debug, PartialEq, Eq)]
+enum Coverage {
+    /// `_` or a variable: matches everything.
+    All,
+    /// A ground literal, normalised so that e.g. `01` and `1` compare equal.
+    Literal(String),
+    /// Anything else (structured patterns, connectives, `=x`).
+    Partial,
+}
+
+fn coverage(pattern: Node, source: &str) -> Coverage {
+    let pattern = unwrap_group(pattern);
+    let text = syntax::text(pattern, source);
+    match pattern.kind() {
+        "wildcard" | "var" => Coverage::All,
+        "long_literal" => match text.parse::<i64>() {
+            Ok(n) => Coverage::Literal(n.to_string()),
+            Err(_) => Coverage::Literal(text.to_string()),
+        },
+        "string_literal" | "bool_literal" | "uri_literal" | "nil" => {
+            Coverage::Literal(text.to_string())
+        }
+        _ => Coverage::Partial,
+    }
+}
+
+/// Report unreachable cases, duplicate literal patterns and non-exhaustive matches.
+pub fn check(doc: &Document, uri: &Url, diagnostics: &mut Vec<Diagnostic>) {
+    let mut matches = Vec::new();
+    collect_matches(doc.tree.root_node(), &mut matches);
+    for node in matches {
+        check_match(node, &doc.source, uri, diagnostics);
+    }
+}
+
+fn collect_matches<'a>(node: Node<'a>, out: &mut Vec<Node<'a>>) {
+    if node.kind() == "match" {
+        out.push(node);
+    }
+    for child in named_children(node) {
+        collect_matches(child, out);
+    }
+}
+
+fn check_match(node: Node, source: &str, uri: &Url, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(cases) = node.child_by_field_name("cases") else {
+        return;
+    };
+    let cases: Vec<(Node, Node)> = named_children(cases)
+        .into_iter()
+        .filter(|c| c.kind() == "case")
+        .filter_map(|c| Some((c, c.child_by_field_name("pattern")?)))
+        .collect();
+
+    let related = |node: Node, message: String| {
+        Some(vec![DiagnosticRelatedInformation {
+            location: Location {
+                uri: uri.clone(),
+                range: node_range(node),
+            },
+            message,
+        }])
+    };
+
+    let mut catch_all: Option<Node> = None;
+    let mut literals: Vec<(String, Node)> = Vec::new();
+    for &(case, pattern) in &cases {
+        if let Some(earlier) = catch_all {
+            diagnostics.push(Diagnostic {
+                range: node_range(case),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("unreachable-case".into())),
+                source: Some("rholang-lsp".into()),
+                message: "Unreachable case: an earlier pattern matches everything".into(),
+                related_information: related(
+                    earlier,
+                    format!("`{}` matches every value", syntax::text(earlier, source)),
+                ),
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                ..Default::default()
+            });
+            continue;
+        }
+        match coverage(pattern, source) {
+            Coverage::All => catch_all = Some(pattern),
+            Coverage::Literal(value) => {
+                if let Some((_, first)) = literals.iter().find(|(v, _)| *v == value) {
+                    diagnostics.push(Diagnostic {
+                        range: node_range(pattern),
+                        severity: Some(DiagnosticSeverity::WARNING),
+                        code: Some(NumberOrString::String("duplicate-pattern".into())),
+                        source: Some("rholang-lsp".into()),
+                        message: format!(
+                            "Duplicate pattern `{}`: an earlier case already matches it",
+                            syntax::text(pattern, source)
+                        ),
+                        related_information: related(*first, "first matched here".into()),
+                        tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                        ..Default::default()
+                    });
+                } else {
+                    literals.push((value, pattern));
+                }
+            }
+            Coverage::Partial => {}
+        }
+    }
+
+    if catch_all.is_some() || cases.is_empty() {
+        return;
+    }
+    let covers = |v: &str| literals.iter().any(|(l, _)| l == v);
+    let all_literal = cases
+        .iter()
+        .all(|(_, p)| matches!(coverage(*p, source), Coverage::Literal(_)));
+    let all_bool = literals.iter().all(|(v, _)| v == "true" || v == "false");
+
+    let message = if all_literal && all_bool {
+        let missing: Vec<&str> = ["true", "false"]
+            .into_iter()
+            .filter(|v| !covers(v))
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        format!(
+            "Non-exhaustive match over Bool: `{}` is not covered",
+            missing.join("`, `")
+        )
+    } else if all_literal {
+        "Non-exhaustive match: no catch-all case, other values match nothing".to_string()
+    } else {
+        return;
+    };
+
+    diagnostics.push(Diagnostic {
+        range: node_range(node.child(0).unwrap_or(node)),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String("non-exhaustive-match".into())),
+        source: Some("rholang-lsp".into()),
+        message,
+        ..Default::default()
+    });
+}
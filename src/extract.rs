@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::{edit_action, reindent, single_file_edit};
+use crate::declare::{enclosing_new, top_level, wrap_in_new, INDENT};
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::scope::{Scopes, Sort};
+use crate::syntax::{self, named_children};
+
+/// The process whose text is exactly the selection (ignoring surrounding whitespace).
+pub fn selected_process<'a>(doc: &'a Document, range: Range) -> Option<Node<'a>> {
+    let start = doc.offset_at(range.start.line as usize, range.start.character as usize);
+    let end = doc.offset_at(range.end.line as usize, range.end.character as usize);
+    let selected = doc.source.get(start..end)?;
+    let start = start + (selected.len() - selected.trim_start().len());
+    let end = end - (selected.len() - selected.trim_end().len());
+    if start >= end {
+        return None;
+    }
+    let mut node = doc
+        .tree
+        .root_node()
+        .named_descendant_for_byte_range(start, end)?;
+    if node.start_byte() != start || node.end_byte() != end {
+        return None;
+    }
+    while let Some(parent) = node.parent() {
+        if parent.start_byte() != start || parent.end_byte() != end {
+            break;
+        }
+        node = parent;
+    }
+    is_process_position(node).then_some(node)
+}
+
+/// Whether `node` stands as a process of its own: a branch of a `|`, a block's
+/// contents or the body of a binder or conditional.
+pub fn is_process_position(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let is_field = |name: &str| {
+        parent
+            .child_by_field_name(name)
+            .is_some_and(|n| n.id() == node.id())
+    };
+    matches!(parent.kind(), "par" | "block" | "source_file")
+        || ["proc", "body", "ifTrue", "alternative"]
+            .iter()
+            .any(|f| is_field(f))
+}
+
+/// A variable name not used anywhere in the document, starting with `base`.
+pub fn fresh_name(doc: &Document, base: &str) -> String {
+    let mut taken = HashSet::new();
+    collect_vars(doc.tree.root_node(), &doc.source, &mut taken);
+    (1..)
+        .map(|i| {
+            if i == 1 {
+                base.to_string()
+            } else {
+                format!("{base}{i}")
+            }
+        })
+        .find(|n| !taken.contains(n.as_str()))
+        .unwrap_or_default()
+}
+
+fn collect_vars<'a>(node: Node, source: &'a str, taken: &mut HashSet<&'a str>) {
+    if node.kind() == "var" {
+        taken.insert(syntax::text(node, source));
+    }
+    for child in named_children(node) {
+        collect_vars(child, source, taken);
+    }
+}
+
+/// Whether `inner` lies within `outer`.
+fn within(inner: Node, outer: Node) -> bool {
+    outer.start_byte() <= inner.start_byte() && inner.end_byte() <= outer.end_byte()
+}
+
+/// Extract the selected process into a contract on a fresh private name, declared
+/// in the nearest enclosing `new` (or a new one around the top-level process).
+/// Variables bound between that `new` and the selection become formals.
+pub fn actions(doc: &Document, uri: &Url, range: Range) -> Vec<CodeActionOrCommand> {
+    let Some(process) = selected_process(doc, range) else {
+        return Vec::new();
+    };
+    let source = &doc.source;
+    let scopes = Scopes::analyze(doc);
+    let new = enclosing_new(process);
+    // The process the contract is placed next to
+    let Some(scope) = new
+        .and_then(|n| n.child_by_field_name("proc"))
+        .or_else(|| top_level(process))
+    else {
+        return Vec::new();
+    };
+
+    let mut formals: Vec<usize> = Vec::new();
+    for u in &scopes.uses {
+        let Some(b) = u.binder else { continue };
+        let binder = scopes.binders[b].node;
+        if within(u.node, process)
+            && within(binder, scope)
+            && !within(binder, process)
+            && !formals.contains(&b)
+        {
+            formals.push(b);
+        }
+    }
+
+    let name = fresh_name(doc, "extracted");
+    let (params, args): (Vec<String>, Vec<String>) = formals
+        .iter()
+        .map(|&b| {
+            let binder = &scopes.binders[b];
+            match binder.sort {
+                Sort::Name => (binder.name.to_string(), format!("*{}", binder.name)),
+                Sort::Process => (format!("@{}", binder.name), binder.name.to_string()),
+            }
+        })
+        .unzip();
+    let call = format!("{name}!({})", args.join(", "));
+
+    // The scope's text with the selection replaced by the call
+    let scope_text = format!(
+        "{}{call}{}",
+        &source[scope.start_byte()..process.start_byte()],
+        &source[process.end_byte()..scope.end_byte()]
+    );
+    let indent = match scope.kind() {
+        "block" => scope
+            .child(1)
+            .filter(|c| c.kind() != "}")
+            .map_or(scope.start_position().column + INDENT.len(), |c| {
+                c.start_position().column
+            }),
+        _ => scope.start_position().column,
+    };
+    let indent = " ".repeat(indent);
+    let body = reindent(
+        syntax::text(process, source),
+        process.start_position().column,
+        &format!("{indent}{INDENT}"),
+    );
+    let contract = format!(
+        "contract {name}({}) = {{\n{body}\n{indent}}}",
+        params.join(", ")
+    );
+
+    let mut edits = Vec::new();
+    match new {
+        Some(new) => {
+            let Some(decls) = new.child_by_field_name("decls") else {
+                return Vec::new();
+            };
+            edits.push(TextEdit {
+                range: Range {
+                    start: node_range(decls).end,
+                    end: node_range(decls).end,
+                },
+                new_text: format!(", {name}"),
+            });
+            let new_text = match scope_text.strip_prefix('{') {
+                Some(rest) if scope.kind() == "block" => format!("{{\n{indent}{contract} |{rest}"),
+                _ => format!("{contract} |\n{indent}{scope_text}"),
+            };
+            edits.push(TextEdit {
+                range: node_range(scope),
+                new_text,
+            });
+        }
+        None => {
+            let body = format!("{contract} |\n{indent}{scope_text}");
+            edits.push(wrap_in_new(scope, &name, &body));
+        }
+    }
+    vec![edit_action(
+        format!("Extract process into contract `{name}`"),
+        CodeActionKind::REFACTOR_EXTRACT,
+        single_file_edit(uri, edits),
+    )]
+}
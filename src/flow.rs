@@ -0,0 +1,136 @@
+use tree_sitter::Node;
+
+use crate::scope::{Channel, Scopes};
+use crate::syntax::{
+    self, bind_source, decl_values, is_bind, is_send, let_decls, names_list, pattern_var,
+    send_args, send_channel,
+};
+
+/// A receiver: a bind or contract, with the channel it listens on and its formals.
+pub struct Receiver<'a> {
+    pub channel: Channel<'a>,
+    pub names: Vec<Node<'a>>,
+}
+
+/// The places a value moves from one expression to a name: sends into receivers
+/// and `let` declarations.
+#[derive(Default)]
+pub struct Sites<'a> {
+    /// Every send with a channel, resolved or not.
+    pub sends: Vec<Node<'a>>,
+    pub receivers: Vec<Receiver<'a>>,
+    /// `let` names paired with the values they bind.
+    pub lets: Vec<(Node<'a>, Node<'a>)>,
+}
+
+impl<'a> Sites<'a> {
+    pub fn collect(root: Node<'a>, scopes: &Scopes<'a>) -> Self {
+        let mut sites = Sites::default();
+        sites.walk(root, scopes);
+        sites
+    }
+
+    fn walk(&mut self, node: Node<'a>, scopes: &Scopes<'a>) {
+        if is_send(node) && send_channel(node).is_some() {
+            self.sends.push(node);
+        } else if is_bind(node) {
+            if let Some(channel) = bind_source(node).and_then(|c| scopes.channel(c)) {
+                self.receivers.push(Receiver {
+                    channel,
+                    names: node
+                        .child_by_field_name("names")
+                        .map(names_list)
+                        .unwrap_or_default(),
+                });
+            }
+        } else if node.kind() == "contract" {
+            if let Some(channel) = node
+                .child_by_field_name("name")
+                .and_then(|c| scopes.channel(c))
+            {
+                self.receivers.push(Receiver {
+                    channel,
+                    names: node
+                        .child_by_field_name("formals")
+                        .map(names_list)
+                        .unwrap_or_default(),
+                });
+            }
+        } else if node.kind() == "let" {
+            if let Some(decls) = node.child_by_field_name("decls") {
+                for decl in let_decls(decls) {
+                    let names = decl
+                        .child_by_field_name("names")
+                        .map(names_list)
+                        .unwrap_or_default();
+                    self.lets.extend(names.into_iter().zip(decl_values(decl)));
+                }
+            }
+        }
+        for child in syntax::named_children(node) {
+            self.walk(child, scopes);
+        }
+    }
+}
+
+/// A fact tracked per binder, such as a type or a bundle capability.
+pub trait Facts<T> {
+    /// The fact known for an expression, given what has been bound so far.
+    fn value_of(&self, node: Node) -> Option<T>;
+    /// Record a fact for the binder of `var`; true if it changed.
+    fn set(&mut self, var: Node, value: T) -> bool;
+}
+
+/// Propagate facts through `let` and receive bindings until nothing changes.
+///
+/// A formal takes a fact only when every send that can reach it is known and
+/// agrees; a single unknown sender leaves it unknown.
+pub fn propagate<'a, T, F>(sites: &Sites<'a>, scopes: &Scopes<'a>, facts: &mut F)
+where
+    T: Copy + PartialEq,
+    F: Facts<T>,
+{
+    let sends: Vec<(Option<Channel>, Vec<Node>)> = sites
+        .sends
+        .iter()
+        .map(|send| {
+            let channel = send_channel(*send).and_then(|c| scopes.channel(c));
+            (channel, send_args(*send))
+        })
+        .collect();
+
+    // Each round follows one more hop
+    for _ in 0..8 {
+        let mut changed = false;
+        for &(name, value) in &sites.lets {
+            if let (Some(var), Some(fact)) = (pattern_var(name), facts.value_of(value)) {
+                changed |= facts.set(var, fact);
+            }
+        }
+        for receiver in &sites.receivers {
+            for (i, name) in receiver.names.iter().enumerate() {
+                let Some(var) = pattern_var(*name) else {
+                    continue;
+                };
+                let values: Option<Vec<T>> = sends
+                    .iter()
+                    .filter(|(channel, args)| {
+                        *channel == Some(receiver.channel) && args.len() == receiver.names.len()
+                    })
+                    .map(|(_, args)| facts.value_of(args[i]))
+                    .collect();
+                let Some(values) = values else {
+                    continue;
+                };
+                if let Some(&first) = values.first() {
+                    if values.iter().all(|v| *v == first) {
+                        changed |= facts.set(var, first);
+                    }
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::{edit_action, remove_items, single_file_edit, unwrap_edits};
+use crate::declare::{enclosing_new, wrap_in_new};
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::extract::{fresh_name, is_process_position, selected_process};
+use crate::scope::Scopes;
+use crate::syntax::{self, named_children, receipt_groups, unwrap_group};
+
+/// Whether `new` can move between `parent` and its `child` without changing how many
+/// names it creates: not into or out of a contract or persistent receive body (run
+/// once per message), nor into the data of a send or pattern.
+fn transparent(child: Node, parent: Node) -> bool {
+    let replicated = match parent.kind() {
+        "contract" => true,
+        "input" => parent
+            .child_by_field_name("receipts")
+            .map(receipt_groups)
+            .unwrap_or_default()
+            .iter()
+            .flatten()
+            .any(|b| b.kind() == "repeated_bind"),
+        _ => false,
+    };
+    !replicated
+        && (is_process_position(child)
+            || matches!(
+                (parent.kind(), child.kind()),
+                ("match", "cases") | ("cases", "case")
+            ))
+}
+
+fn within(inner: Node, outer: Node) -> bool {
+    outer.start_byte() <= inner.start_byte() && inner.end_byte() <= outer.end_byte()
+}
+
+/// The `name_decl`s of a `new`.
+fn name_decls(new: Node) -> Vec<Node> {
+    new.child_by_field_name("decls")
+        .map(named_children)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|d| d.kind() == "name_decl")
+        .collect()
+}
+
+/// Edits deleting `decl` from `new`, unwrapping the `new` when it was the only one.
+fn remove_decl(new: Node, decl: Node) -> Vec<TextEdit> {
+    let decls = name_decls(new);
+    match new.child_by_field_name("proc") {
+        Some(body) if decls.len() == 1 => unwrap_edits(new, body),
+        _ => {
+            let flags: Vec<bool> = decls.iter().map(|d| d.id() == decl.id()).collect();
+            remove_items(&decls, &flags)
+        }
+    }
+}
+
+/// Wrap the selected process in `new` of a fresh name, and move the declaration under
+/// the cursor to the enclosing `new` or down to the smallest process using it.
+pub fn actions(doc: &Document, uri: &Url, range: Range) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+    if let Some(process) = selected_process(doc, range) {
+        let name = fresh_name(doc, "ch");
+        let edit = wrap_in_new(process, &name, syntax::text(process, &doc.source));
+        actions.push(edit_action(
+            format!("Wrap in `new {name}`"),
+            CodeActionKind::REFACTOR_REWRITE,
+            single_file_edit(uri, vec![edit]),
+        ));
+    }
+
+    let offset = doc.offset_at(range.start.line as usize, range.start.character as usize);
+    let Some(mut decl) = doc
+        .tree
+        .root_node()
+        .named_descendant_for_byte_range(offset, offset)
+    else {
+        return actions;
+    };
+    while decl.kind() != "name_decl" {
+        let Some(parent) = decl.parent() else {
+            return actions;
+        };
+        decl = parent;
+    }
+    let (Some(var), Some(new)) = (decl.named_child(0), decl.parent().and_then(|d| d.parent()))
+    else {
+        return actions;
+    };
+    if new.kind() != "new" {
+        return actions;
+    }
+    let name = syntax::text(var, &doc.source);
+    if let Some(edits) = hoist(doc, new, decl, name) {
+        actions.push(edit_action(
+            format!("Hoist `{name}` to the enclosing `new`"),
+            CodeActionKind::REFACTOR_REWRITE,
+            single_file_edit(uri, edits),
+        ));
+    }
+    if let Some(edits) = sink(doc, new, decl, var) {
+        actions.push(edit_action(
+            format!("Move `{name}` into the process that uses it"),
+            CodeActionKind::REFACTOR_REWRITE,
+            single_file_edit(uri, edits),
+        ));
+    }
+    actions
+}
+
+/// Move `decl` to the next `new` out, provided nothing between them repeats the body
+/// and the name does not already occur in the outer `new` (which would then capture it).
+fn hoist(doc: &Document, new: Node, decl: Node, name: &str) -> Option<Vec<TextEdit>> {
+    let outer = enclosing_new(new)?;
+    let outer_body = outer.child_by_field_name("proc")?;
+    let mut child = new;
+    while child.id() != outer_body.id() {
+        let parent = child.parent()?;
+        if !transparent(child, parent) {
+            return None;
+        }
+        child = parent;
+    }
+
+    let mut vars = Vec::new();
+    collect_vars(outer, &doc.source, name, &mut vars);
+    if vars.iter().any(|v| !within(*v, new)) {
+        return None;
+    }
+    let decls = outer.child_by_field_name("decls")?;
+    let mut edits = remove_decl(new, decl);
+    edits.push(TextEdit {
+        range: Range {
+            start: node_range(decls).end,
+            end: node_range(decls).end,
+        },
+        new_text: format!(", {}", syntax::text(decl, &doc.source)),
+    });
+    Some(edits)
+}
+
+fn collect_vars<'a>(node: Node<'a>, source: &str, name: &str, out: &mut Vec<Node<'a>>) {
+    if node.kind() == "var" && syntax::text(node, source) == name {
+        out.push(node);
+    }
+    for child in named_children(node) {
+        collect_vars(child, source, name, out);
+    }
+}
+
+/// Move `decl` into the smallest process of the `new` body containing every use of
+/// the name, descending only through constructs that run their body at most once.
+fn sink(doc: &Document, new: Node, decl: Node, var: Node) -> Option<Vec<TextEdit>> {
+    let scopes = Scopes::analyze(doc);
+    let binder = scopes.binder_of(var)?;
+    let uses: Vec<Node> = scopes.uses_of(binder).map(|u| u.node).collect();
+    if uses.is_empty() {
+        return None;
+    }
+    let body = unwrap_group(new.child_by_field_name("proc")?);
+    let mut current = body;
+    let mut target = body;
+    while let Some(child) = named_children(current)
+        .into_iter()
+        .find(|c| uses.iter().all(|u| within(*u, *c)))
+    {
+        if !transparent(child, current) {
+            break;
+        }
+        if is_process_position(child) {
+            target = child;
+        }
+        current = child;
+    }
+    if target.byte_range() == body.byte_range() {
+        return None;
+    }
+
+    let mut edits = remove_decl(new, decl);
+    edits.push(wrap_in_new(
+        target,
+        syntax::text(decl, &doc.source),
+        syntax::text(target, &doc.source),
+    ));
+    Some(edits)
+}
@@ -1,7 +1,13 @@
 use tower_lsp::lsp_types::*;
 use tree_sitter::Point;
 
+use crate::constants;
 use crate::document::Document;
+use crate::methods;
+use crate::registry;
+use crate::scope::Scopes;
+use crate::system;
+use crate::types::Inference;
 
 pub fn hover(doc: &Document, pos: Position) -> Option<Hover> {
     let point = Point {
@@ -64,12 +70,40 @@ pub fn hover(doc: &Document, pos: Position) -> Option<Hover> {
         _ => kind.to_string(),
     };
 
+    // Inferred ground type, for names the type of the process they carry
+    let scopes = Scopes::analyze(doc);
+    let inference = Inference::new(doc, &scopes);
+    let ty = match inference.type_of(node) {
+        Some(ty) => Some(format!("`{ty}`")),
+        None if kind == "var" => inference
+            .bound_type(node)
+            .map(|ty| format!("`{ty}` (via `*{text}`)")),
+        None => None,
+    };
+
     // Check for preceding comment
     let comment = find_preceding_comment(node, source);
 
     let mut parts = Vec::new();
     parts.push(format!("```rholang\n{text}\n```"));
     parts.push(format!("**{context}** (`{kind}`)"));
+    if let Some(ty) = ty {
+        parts.push(format!("**type** {ty}"));
+    }
+    if let Some(value) = constants::hover_value(doc, &scopes, node) {
+        parts.push(value);
+    }
+    if context == "method name" {
+        if let Some(method) = methods::lookup(text) {
+            parts.push(format!("---\n{}", method.markdown()));
+        }
+    }
+    if let Some(system_doc) = system::hover_doc(doc, &scopes, node) {
+        parts.push(format!("---\n{system_doc}"));
+    }
+    if let Some(id_doc) = registry::hover_doc(doc, node) {
+        parts.push(format!("---\n{id_doc}"));
+    }
     if let Some(c) = comment {
         parts.push(format!("---\n{c}"));
     }
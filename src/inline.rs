@@ -0,0 +1,281 @@
+use std::collections::{HashMap, HashSet};
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::{edit_action, reindent, single_file_edit};
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::extract::fresh_name;
+use crate::scope::{Channel, Scopes};
+use crate::syntax::{self, has_remainder, is_send, named_children, names_list, pattern_var};
+
+/// Expressions that can stand in for a variable without parentheses.
+const ATOMS: &[&str] = &[
+    "var",
+    "long_literal",
+    "string_literal",
+    "bool_literal",
+    "uri_literal",
+    "nil",
+    "list",
+    "set",
+    "map",
+    "tuple",
+    "block",
+    "parenthesized",
+    "eval",
+];
+
+#[derive(Default)]
+struct Sites<'a> {
+    contracts: Vec<Node<'a>>,
+    sends: Vec<Node<'a>>,
+}
+
+fn collect<'a>(node: Node<'a>, sites: &mut Sites<'a>) {
+    if node.kind() == "contract" {
+        sites.contracts.push(node);
+    } else if node.kind() == "send" {
+        sites.sends.push(node);
+    }
+    for child in named_children(node) {
+        collect(child, sites);
+    }
+}
+
+/// Inline a contract into a linear send to it: the send under the cursor, or the
+/// only send when the cursor is on the contract.
+pub fn actions(doc: &Document, uri: &Url, range: Range) -> Vec<CodeActionOrCommand> {
+    let offset = doc.offset_at(range.start.line as usize, range.start.character as usize);
+    let scopes = Scopes::analyze(doc);
+    let mut sites = Sites::default();
+    collect(doc.tree.root_node(), &mut sites);
+    let channel_of = |node: Node| {
+        let channel = match node.kind() {
+            "contract" => node.child_by_field_name("name"),
+            _ => syntax::send_channel(node),
+        };
+        channel.and_then(|c| scopes.channel(c))
+    };
+
+    let Some(mut node) = doc
+        .tree
+        .root_node()
+        .named_descendant_for_byte_range(offset, offset)
+    else {
+        return Vec::new();
+    };
+    while !(node.kind() == "contract" || is_send(node)) {
+        let Some(parent) = node.parent() else {
+            return Vec::new();
+        };
+        node = parent;
+    }
+    let Some(channel) = channel_of(node) else {
+        return Vec::new();
+    };
+    let calls: Vec<Node> = sites
+        .sends
+        .iter()
+        .copied()
+        .filter(|s| channel_of(*s) == Some(channel))
+        .collect();
+    let (contract, send) = if node.kind() == "contract" {
+        match calls[..] {
+            [send] => (node, send),
+            _ => return Vec::new(),
+        }
+    } else {
+        let mut contracts = sites
+            .contracts
+            .iter()
+            .filter(|c| channel_of(**c) == Some(channel));
+        match (contracts.next(), contracts.next()) {
+            (Some(contract), None) => (*contract, node),
+            _ => return Vec::new(),
+        }
+    };
+    let recursive =
+        contract.start_byte() <= send.start_byte() && send.end_byte() <= contract.end_byte();
+    if recursive || !matches!(syntax::send_operator(send, &doc.source), Some(("!", _))) {
+        return Vec::new();
+    }
+
+    let Some(mut edits) = inline(doc, &scopes, contract, send) else {
+        return Vec::new();
+    };
+    // Drop the contract once nothing else can reach its private name
+    if let Channel::Bound(b) = channel {
+        let only_call = scopes.uses_of(b).all(|u| {
+            u.node
+                .parent()
+                .is_some_and(|p| p.id() == contract.id() || p.id() == send.id())
+        });
+        if only_call && calls.len() == 1 {
+            edits.push(remove_process(contract));
+        }
+    }
+    let name = contract
+        .child_by_field_name("name")
+        .map_or("", |n| syntax::text(n, &doc.source));
+    vec![edit_action(
+        format!("Inline contract `{name}`"),
+        CodeActionKind::REFACTOR_INLINE,
+        single_file_edit(uri, edits),
+    )]
+}
+
+/// The edit replacing `send` with the contract body, with the arguments substituted
+/// for the formals and body binders renamed where they would capture an argument's
+/// variables. `None` when a name the body takes from around the contract would
+/// resolve differently at the send.
+fn inline(doc: &Document, scopes: &Scopes, contract: Node, send: Node) -> Option<Vec<TextEdit>> {
+    let source = &doc.source;
+    let formals = contract.child_by_field_name("formals");
+    if formals.is_some_and(has_remainder) {
+        return None;
+    }
+    let formals = formals.map(names_list).unwrap_or_default();
+    let args = syntax::send_args(send);
+    if formals.len() != args.len() {
+        return None;
+    }
+    let body = contract.child_by_field_name("proc")?;
+
+    // Names the body takes from around the contract must mean the same at the send
+    let escapes = scopes.uses.iter().any(|u| {
+        let inside = |node: Node| {
+            contract.start_byte() <= node.start_byte() && node.end_byte() <= contract.end_byte()
+        };
+        inside(u.node)
+            && !u.binder.is_some_and(|b| inside(scopes.binders[b].node))
+            && scopes.visible_at(syntax::text(u.node, source), send) != u.binder
+    });
+    if escapes {
+        return None;
+    }
+
+    // (start, end, replacement) within the body
+    let mut replacements: Vec<(usize, usize, String)> = Vec::new();
+    for (formal, arg) in formals.iter().zip(&args) {
+        let var = pattern_var(*formal)?;
+        let binder = scopes.binder_of(var)?;
+        let text = syntax::text(*arg, source);
+        let atom = if ATOMS.contains(&arg.kind()) {
+            text.to_string()
+        } else {
+            format!("({text})")
+        };
+        for u in scopes.uses_of(binder) {
+            let replacement = if formal.kind() == "quote" {
+                (u.node, atom.clone())
+            } else if let Some(eval) = u.node.parent().filter(|p| p.kind() == "eval") {
+                (eval, atom.clone())
+            } else if arg.kind() == "eval" {
+                // `*y` passed for a name is `y` again
+                (u.node, text[1..].trim_start().to_string())
+            } else if ATOMS.contains(&arg.kind()) && arg.kind() != "block" {
+                (u.node, format!("@{text}"))
+            } else {
+                (u.node, format!("@{{{text}}}"))
+            };
+            replacements.push((
+                replacement.0.start_byte(),
+                replacement.0.end_byte(),
+                replacement.1,
+            ));
+        }
+    }
+
+    let mut arg_vars = HashSet::new();
+    for arg in &args {
+        collect_var_names(*arg, source, &mut arg_vars);
+    }
+    let mut renamed: HashMap<&str, String> = HashMap::new();
+    for (b, binder) in scopes.binders.iter().enumerate() {
+        let inside = body.start_byte() <= binder.node.start_byte()
+            && binder.node.end_byte() <= body.end_byte();
+        if !inside || !arg_vars.contains(binder.name) {
+            continue;
+        }
+        let fresh = renamed
+            .entry(binder.name)
+            .or_insert_with(|| fresh_name(doc, binder.name));
+        replacements.push((
+            binder.node.start_byte(),
+            binder.node.end_byte(),
+            fresh.clone(),
+        ));
+        for u in scopes.uses_of(b) {
+            replacements.push((u.node.start_byte(), u.node.end_byte(), fresh.clone()));
+        }
+    }
+
+    replacements.sort_by_key(|r| r.0);
+    let mut text = String::new();
+    let mut at = body.start_byte();
+    for (start, end, replacement) in replacements {
+        if start < at {
+            continue;
+        }
+        text.push_str(&source[at..start]);
+        text.push_str(&replacement);
+        at = end;
+    }
+    text.push_str(&source[at..body.end_byte()]);
+
+    // Continuation lines move from the contract's indentation to the send's
+    let indent = " ".repeat(line_indent(source, send.start_position().row));
+    let text = reindent(
+        &text,
+        line_indent(source, body.start_position().row),
+        &indent,
+    );
+    Some(vec![TextEdit {
+        range: node_range(send),
+        new_text: text.trim_start().to_string(),
+    }])
+}
+
+/// Number of leading spaces on a line.
+fn line_indent(source: &str, row: usize) -> usize {
+    let line = source.lines().nth(row).unwrap_or_default();
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn collect_var_names<'a>(node: Node, source: &'a str, names: &mut HashSet<&'a str>) {
+    if node.kind() == "var" {
+        names.insert(syntax::text(node, source));
+    }
+    for child in named_children(node) {
+        collect_var_names(child, source, names);
+    }
+}
+
+/// The edit deleting a process together with the `|` joining it to its neighbour.
+fn remove_process(process: Node) -> TextEdit {
+    let parent = process.parent().filter(|p| p.kind() == "par");
+    let siblings = parent.map(named_children).unwrap_or_default();
+    let index = siblings.iter().position(|s| s.id() == process.id());
+    let range = match index {
+        Some(0) if siblings.len() > 1 => Range {
+            start: node_range(process).start,
+            end: node_range(siblings[1]).start,
+        },
+        Some(i) if i > 0 => Range {
+            start: node_range(siblings[i - 1]).end,
+            end: node_range(process).end,
+        },
+        _ => {
+            return TextEdit {
+                range: node_range(process),
+                new_text: "Nil".into(),
+            }
+        }
+    };
+    TextEdit {
+        range,
+        new_text: String::new(),
+    }
+}
@@ -0,0 +1,204 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::scope::Scopes;
+use crate::syntax::{self, binary_parts, named_children, unwrap_group};
+use crate::types::{Inference, Type};
+
+/// A `${key}` placeholder, with the byte range of the whole placeholder in the source.
+pub struct Placeholder<'a> {
+    pub key: &'a str,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Placeholders in a string literal.
+pub fn placeholders<'a>(literal: Node, source: &'a str) -> Vec<Placeholder<'a>> {
+    let text = syntax::text(literal, source);
+    let base = literal.start_byte();
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = text[from..].find("${").map(|i| from + i) {
+        let Some(close) = text[open..].find('}').map(|i| open + i) else {
+            break;
+        };
+        found.push(Placeholder {
+            key: &text[open + 2..close],
+            start: base + open,
+            end: base + close + 1,
+        });
+        from = close + 1;
+    }
+    found
+}
+
+/// The value a key denotes once its escapes are resolved, so `"a\"b"` and a
+/// placeholder written `${a\"b}` compare equal.
+fn unescape(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Whether `node` is the template of a `%%` interpolation, possibly parenthesized.
+pub fn is_template(node: Node) -> bool {
+    if node.kind() != "string_literal" {
+        return false;
+    }
+    let mut parent = node.parent();
+    while let Some(group) = parent.filter(|p| matches!(p.kind(), "block" | "parenthesized")) {
+        parent = group.parent();
+    }
+    parent
+        .and_then(binary_parts)
+        .is_some_and(|(left, op, _)| op == "%%" && unwrap_group(left).id() == node.id())
+}
+
+/// The map literal a `%%` interpolates with: written inline or bound by a `let`.
+fn map_literal<'a>(node: Node<'a>, scopes: &Scopes<'a>) -> Option<Node<'a>> {
+    let node = unwrap_group(node);
+    match node.kind() {
+        "map" => Some(node),
+        "var" => map_literal(scopes.let_value(node)?, scopes),
+        "eval" => map_literal(
+            scopes.let_value(named_children(node).into_iter().next()?)?,
+            scopes,
+        ),
+        _ => None,
+    }
+}
+
+/// Check `"..." %% {...}`: placeholders without a key, keys never used and values
+/// that cannot be interpolated.
+pub fn check(
+    doc: &Document,
+    scopes: &Scopes,
+    inference: &Inference,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    walk(doc.tree.root_node(), doc, scopes, inference, diagnostics);
+}
+
+fn walk(
+    node: Node,
+    doc: &Document,
+    scopes: &Scopes,
+    inference: &Inference,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if let Some((template, "%%", values)) = binary_parts(node) {
+        let template = unwrap_group(template);
+        if let (true, Some(map)) = (
+            template.kind() == "string_literal",
+            map_literal(values, scopes),
+        ) {
+            check_interpolation(template, map, doc, inference, diagnostics);
+        }
+    }
+    for child in named_children(node) {
+        walk(child, doc, scopes, inference, diagnostics);
+    }
+}
+
+fn check_interpolation(
+    template: Node,
+    map: Node,
+    doc: &Document,
+    inference: &Inference,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let source = &doc.source;
+    let mut report = |range: Range, message: String, tags: Option<Vec<DiagnosticTag>>| {
+        diagnostics.push(Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("interpolation".into())),
+            source: Some("rholang-lsp".into()),
+            message,
+            tags,
+            ..Default::default()
+        });
+    };
+
+    let placeholders = placeholders(template, source);
+    let wanted: Vec<String> = placeholders.iter().map(|p| unescape(p.key)).collect();
+    let pairs: Vec<Node> = named_children(map)
+        .into_iter()
+        .filter(|n| n.kind() == "key_value_pair")
+        .collect();
+    // Keys only known at run time could match any placeholder
+    let mut keys = Vec::new();
+    let mut all_literal = true;
+    for pair in &pairs {
+        match pair.child_by_field_name("key").map(unwrap_group) {
+            Some(key) if key.kind() == "string_literal" => {
+                let text = syntax::text(key, source);
+                let raw = text
+                    .strip_prefix('"')
+                    .and_then(|t| t.strip_suffix('"'))
+                    .unwrap_or(text);
+                keys.push((unescape(raw), key));
+            }
+            _ => all_literal = false,
+        }
+    }
+
+    if all_literal {
+        for (placeholder, _) in placeholders
+            .iter()
+            .zip(&wanted)
+            .filter(|(_, w)| !keys.iter().any(|(k, _)| k == *w))
+        {
+            let (start_line, start_col) = doc.position_at(placeholder.start);
+            let (end_line, end_col) = doc.position_at(placeholder.end);
+            let range = Range {
+                start: Position::new(start_line as u32, start_col as u32),
+                end: Position::new(end_line as u32, end_col as u32),
+            };
+            report(
+                range,
+                format!("No value for placeholder `${{{}}}`", placeholder.key),
+                None,
+            );
+        }
+    }
+    for (key, node) in &keys {
+        if !wanted.contains(key) {
+            report(
+                node_range(*node),
+                format!("Key \"{key}\" is not used in the template"),
+                Some(vec![DiagnosticTag::UNNECESSARY]),
+            );
+        }
+    }
+    for pair in &pairs {
+        let Some(value) = pair.child_by_field_name("value") else {
+            continue;
+        };
+        if let Some(ty) = inference
+            .type_of(value)
+            .filter(|t| !matches!(t, Type::String | Type::Int))
+        {
+            report(
+                node_range(value),
+                format!("Only String and Int values can be interpolated, found {ty}"),
+                None,
+            );
+        }
+    }
+}
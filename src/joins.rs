@@ -0,0 +1,211 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::{edit_action, reindent, single_file_edit};
+use crate::declare::INDENT;
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::scope::Scopes;
+use crate::syntax::{self, bind_source, named_children, receipt_groups};
+
+/// Merge a `for` whose body is another `for` into one receive, and split receives with
+/// several receipts or a join back into nested ones. Joins change when messages are
+/// consumed, so those actions say so in their title.
+pub fn actions(doc: &Document, uri: &Url, range: Range) -> Vec<CodeActionOrCommand> {
+    let offset = doc.offset_at(range.start.line as usize, range.start.character as usize);
+    let Some(mut input) = doc
+        .tree
+        .root_node()
+        .named_descendant_for_byte_range(offset, offset)
+    else {
+        return Vec::new();
+    };
+    while input.kind() != "input" {
+        let Some(parent) = input.parent() else {
+            return Vec::new();
+        };
+        input = parent;
+    }
+    let (Some(receipts), Some(body)) = (
+        input.child_by_field_name("receipts"),
+        input.child_by_field_name("proc"),
+    ) else {
+        return Vec::new();
+    };
+    if offset >= body.start_byte() {
+        return Vec::new();
+    }
+
+    let source = &doc.source;
+    let mut rewrites = Vec::new();
+    if let Some(inner) = nested_input(body) {
+        let inner_receipts = inner.child_by_field_name("receipts");
+        if let (Some(inner_receipts), Some(inner_body)) =
+            (inner_receipts, inner.child_by_field_name("proc"))
+        {
+            let sequential = format!(
+                "{}; {}",
+                syntax::text(receipts, source),
+                syntax::text(inner_receipts, source)
+            );
+            rewrites.push((
+                "Merge nested `for` into sequential receive `;`".to_string(),
+                merged(doc, input, inner, inner_body, &sequential),
+            ));
+            if joinable(doc, receipts, inner_receipts) {
+                let join = format!(
+                    "{} & {}",
+                    syntax::text(receipts, source),
+                    syntax::text(inner_receipts, source)
+                );
+                rewrites.push((
+                    "Merge nested `for` into join `&` (changes synchronization: both messages are consumed together)".to_string(),
+                    merged(doc, input, inner, inner_body, &join),
+                ));
+            }
+        }
+    }
+
+    let groups = receipt_groups(receipts);
+    if groups.len() > 1 {
+        let parts: Vec<&str> = named_children(receipts)
+            .into_iter()
+            .map(|r| syntax::text(r, source))
+            .collect();
+        rewrites.push((
+            "Split sequential receive into nested `for`".to_string(),
+            nested(doc, input, body, &parts),
+        ));
+    }
+    let joins: Vec<&Vec<Node>> = groups.iter().filter(|g| g.len() > 1).collect();
+    // Only linear joins split faithfully: nested persistent receives multiply listeners
+    let scopes = Scopes::analyze(doc);
+    let splittable = !joins.is_empty()
+        && joins.iter().all(|g| {
+            g.iter().all(|b| b.kind() == "linear_bind")
+                && (1..g.len()).all(|j| independent(&scopes, source, &g[..j], g[j]))
+        });
+    if splittable {
+        let parts: Vec<&str> = groups
+            .iter()
+            .flatten()
+            .map(|b| syntax::text(*b, source))
+            .collect();
+        rewrites.push((
+            "Split join into nested `for` (changes synchronization: messages are consumed one at a time)".to_string(),
+            nested(doc, input, body, &parts),
+        ));
+    }
+
+    rewrites
+        .into_iter()
+        .map(|(title, edit)| {
+            edit_action(
+                title,
+                CodeActionKind::REFACTOR_REWRITE,
+                single_file_edit(uri, vec![edit]),
+            )
+        })
+        .collect()
+}
+
+/// The `for` that makes up the whole of a body, with no comments or other processes
+/// beside it.
+fn nested_input(body: Node) -> Option<Node> {
+    let inner = match body.kind() {
+        "block" if body.named_child_count() == 1 => body.named_child(0)?,
+        "block" => return None,
+        _ => body,
+    };
+    (inner.kind() == "input").then_some(inner)
+}
+
+/// The edit replacing `outer` with a single `for` over `receipts` and the inner body.
+fn merged(doc: &Document, outer: Node, inner: Node, inner_body: Node, receipts: &str) -> TextEdit {
+    let column = outer.start_position().column;
+    let body = reindent(
+        syntax::text(inner_body, &doc.source),
+        inner.start_position().column,
+        &" ".repeat(column),
+    );
+    TextEdit {
+        range: node_range(outer),
+        new_text: format!("for ({receipts}) {}", body.trim_start()),
+    }
+}
+
+/// Whether two receipts can be joined: a single group of linear binds each, with the
+/// inner channels and patterns independent of what the outer receive binds.
+fn joinable(doc: &Document, outer: Node, inner: Node) -> bool {
+    let (outer_groups, inner_groups) = (receipt_groups(outer), receipt_groups(inner));
+    if outer_groups.len() != 1 || inner_groups.len() != 1 {
+        return false;
+    }
+    let all_linear = outer_groups
+        .iter()
+        .chain(&inner_groups)
+        .flatten()
+        .all(|b| b.kind() == "linear_bind" && bind_source(*b).is_some());
+    if !all_linear {
+        return false;
+    }
+
+    let scopes = Scopes::analyze(doc);
+    independent(&scopes, &doc.source, &[outer], inner)
+}
+
+/// Whether `later` mentions none of the names bound in `earlier`, neither in its
+/// channel nor in its patterns. Nesting `later` inside receives on `earlier` would
+/// otherwise bind those mentions to the new receive.
+fn independent(scopes: &Scopes, source: &str, earlier: &[Node], later: Node) -> bool {
+    let bound: Vec<&str> = scopes
+        .binders
+        .iter()
+        .filter(|b| earlier.iter().any(|e| within(b.node, *e)))
+        .map(|b| b.name)
+        .collect();
+    let mut vars = Vec::new();
+    collect_vars(later, &mut vars);
+    !vars
+        .iter()
+        .any(|v| bound.contains(&syntax::text(*v, source)))
+}
+
+fn collect_vars<'a>(node: Node<'a>, out: &mut Vec<Node<'a>>) {
+    if node.kind() == "var" {
+        out.push(node);
+    }
+    for child in named_children(node) {
+        collect_vars(child, out);
+    }
+}
+
+fn within(inner: Node, outer: Node) -> bool {
+    outer.start_byte() <= inner.start_byte() && inner.end_byte() <= outer.end_byte()
+}
+
+/// The edit replacing `input` with one `for` per receipt in `parts`, nested in order.
+fn nested(doc: &Document, input: Node, body: Node, parts: &[&str]) -> TextEdit {
+    let column = input.start_position().column;
+    let indent = |depth: usize| format!("{}{}", " ".repeat(column), INDENT.repeat(depth));
+    let depth = parts.len() - 1;
+    let mut text = String::new();
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            text.push_str(&indent(i));
+        }
+        text.push_str(&format!("for ({part}) "));
+        if i < depth {
+            text.push_str("{\n");
+        }
+    }
+    let body = reindent(syntax::text(body, &doc.source), column, &indent(depth));
+    text.push_str(body.trim_start());
+    for i in (0..depth).rev() {
+        text.push_str(&format!("\n{}}}", indent(i)));
+    }
+    TextEdit {
+        range: node_range(input),
+        new_text: text,
+    }
+}
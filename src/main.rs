@@ -1,11 +1,44 @@
+mod arity;
 mod backend;
+mod bundles;
+mod code_actions;
+mod completion;
+mod config;
+mod constants;
+mod convert;
+mod deadlock;
+mod declare;
 mod definition;
 mod diagnostics;
 mod document;
+mod duplicates;
+mod exhaustiveness;
+mod extract;
+mod flow;
+mod hoist;
 mod hover;
+mod inline;
+mod interpolation;
+mod joins;
+mod methods;
+mod orphans;
+mod patterns;
+mod registry;
 mod rename;
+mod repair;
+mod returns;
+mod scope;
 mod semantic_tokens;
+mod shadowing;
+mod signature;
+mod sorts;
 mod symbols;
+mod syntax;
+mod system;
+#[cfg(test)]
+mod test_support;
+mod types;
+mod unused;
 
 use clap::Parser;
 use tower_lsp::{LspService, Server};
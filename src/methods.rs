@@ -0,0 +1,291 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::syntax::{self, named_children};
+use crate::types::{Inference, Type};
+
+/// What a built-in method evaluates to.
+#[derive(Debug, Clone, Copy)]
+pub enum Returns {
+    Type(Type),
+    /// Same type as the receiver (`slice`, `union`, ...).
+    Receiver,
+    /// Depends on the contents of the receiver (`nth`, `get`, ...).
+    Element,
+}
+
+/// A built-in method of Rholang ground types.
+pub struct Method {
+    pub name: &'static str,
+    /// Receiver types; empty means any process.
+    pub receivers: &'static [Type],
+    pub params: &'static [&'static str],
+    pub returns: Returns,
+    pub doc: &'static str,
+}
+
+impl Method {
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, self.params.join(", "))
+    }
+
+    pub fn accepts(&self, receiver: Type) -> bool {
+        self.receivers.is_empty() || self.receivers.contains(&receiver)
+    }
+
+    /// Markdown documentation for hover and completion.
+    pub fn markdown(&self) -> String {
+        let on = if self.receivers.is_empty() {
+            "any process".to_string()
+        } else {
+            self.receivers
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        format!(
+            "```rholang\n.{}\n```\n\n{}\n\n*Defined on:* {on}",
+            self.signature(),
+            self.doc
+        )
+    }
+}
+
+pub const METHODS: &[Method] = &[
+    Method {
+        name: "nth",
+        receivers: &[Type::List, Type::Tuple],
+        params: &["index"],
+        returns: Returns::Element,
+        doc: "The element at the zero-based `index`.",
+    },
+    Method {
+        name: "length",
+        receivers: &[Type::String, Type::ByteArray, Type::List],
+        params: &[],
+        returns: Returns::Type(Type::Int),
+        doc: "The number of characters, bytes or elements.",
+    },
+    Method {
+        name: "size",
+        receivers: &[Type::Map, Type::Set],
+        params: &[],
+        returns: Returns::Type(Type::Int),
+        doc: "The number of entries.",
+    },
+    Method {
+        name: "slice",
+        receivers: &[Type::String, Type::ByteArray, Type::List],
+        params: &["from", "until"],
+        returns: Returns::Receiver,
+        doc: "The part between index `from` (inclusive) and `until` (exclusive).",
+    },
+    Method {
+        name: "take",
+        receivers: &[Type::List],
+        params: &["n"],
+        returns: Returns::Receiver,
+        doc: "The first `n` elements.",
+    },
+    Method {
+        name: "toByteArray",
+        receivers: &[],
+        params: &[],
+        returns: Returns::Type(Type::ByteArray),
+        doc: "The protobuf serialization of the process.",
+    },
+    Method {
+        name: "hexToBytes",
+        receivers: &[Type::String],
+        params: &[],
+        returns: Returns::Type(Type::ByteArray),
+        doc: "Decode a hexadecimal string into bytes.",
+    },
+    Method {
+        name: "bytesToHex",
+        receivers: &[Type::ByteArray],
+        params: &[],
+        returns: Returns::Type(Type::String),
+        doc: "Encode bytes as a hexadecimal string.",
+    },
+    Method {
+        name: "toUtf8Bytes",
+        receivers: &[Type::String],
+        params: &[],
+        returns: Returns::Type(Type::ByteArray),
+        doc: "Encode the string as UTF-8 bytes.",
+    },
+    Method {
+        name: "toString",
+        receivers: &[Type::Int, Type::String, Type::Bool, Type::Uri],
+        params: &[],
+        returns: Returns::Type(Type::String),
+        doc: "The textual representation of the value.",
+    },
+    Method {
+        name: "contains",
+        receivers: &[Type::Map, Type::Set],
+        params: &["element"],
+        returns: Returns::Type(Type::Bool),
+        doc: "Whether the set holds `element`, or the map has `element` as a key.",
+    },
+    Method {
+        name: "get",
+        receivers: &[Type::Map],
+        params: &["key"],
+        returns: Returns::Element,
+        doc: "The value stored under `key`, or `Nil` when absent.",
+    },
+    Method {
+        name: "getOrElse",
+        receivers: &[Type::Map],
+        params: &["key", "default"],
+        returns: Returns::Element,
+        doc: "The value stored under `key`, or `default` when absent.",
+    },
+    Method {
+        name: "set",
+        receivers: &[Type::Map],
+        params: &["key", "value"],
+        returns: Returns::Type(Type::Map),
+        doc: "A copy of the map with `key` bound to `value`.",
+    },
+    Method {
+        name: "keys",
+        receivers: &[Type::Map],
+        params: &[],
+        returns: Returns::Type(Type::Set),
+        doc: "The set of keys of the map.",
+    },
+    Method {
+        name: "add",
+        receivers: &[Type::Set],
+        params: &["element"],
+        returns: Returns::Type(Type::Set),
+        doc: "A copy of the set with `element` added.",
+    },
+    Method {
+        name: "delete",
+        receivers: &[Type::Map, Type::Set],
+        params: &["element"],
+        returns: Returns::Receiver,
+        doc: "A copy without `element` (for maps: without the key `element`).",
+    },
+    Method {
+        name: "union",
+        receivers: &[Type::Map, Type::Set],
+        params: &["other"],
+        returns: Returns::Receiver,
+        doc: "All entries of both collections; for maps, `other` wins on shared keys.",
+    },
+    Method {
+        name: "diff",
+        receivers: &[Type::Map, Type::Set],
+        params: &["other"],
+        returns: Returns::Receiver,
+        doc: "The entries (for maps: keys) not present in `other`.",
+    },
+    Method {
+        name: "toList",
+        receivers: &[Type::List, Type::Set, Type::Map, Type::Tuple],
+        params: &[],
+        returns: Returns::Type(Type::List),
+        doc: "The elements as a list; map entries become `(key, value)` tuples.",
+    },
+    Method {
+        name: "toSet",
+        receivers: &[Type::List, Type::Map],
+        params: &[],
+        returns: Returns::Type(Type::Set),
+        doc: "The elements as a set, dropping duplicates.",
+    },
+    Method {
+        name: "toMap",
+        receivers: &[Type::List, Type::Set],
+        params: &[],
+        returns: Returns::Type(Type::Map),
+        doc: "A map built from `(key, value)` tuples.",
+    },
+];
+
+/// Look up a built-in method by name.
+pub fn lookup(name: &str) -> Option<&'static Method> {
+    METHODS.iter().find(|m| m.name == name)
+}
+
+/// The argument expressions of a `method` node.
+pub fn method_args(node: Node) -> Vec<Node> {
+    let receiver = node.child_by_field_name("receiver").map(|n| n.id());
+    let name = node.child_by_field_name("name").map(|n| n.id());
+    named_children(node)
+        .into_iter()
+        .filter(|n| Some(n.id()) != receiver && Some(n.id()) != name)
+        .flat_map(|n| {
+            if n.kind() == "args" {
+                named_children(n)
+            } else {
+                vec![n]
+            }
+        })
+        .collect()
+}
+
+/// Report unknown methods, wrong argument counts and receivers of the wrong type.
+pub fn check(doc: &Document, inference: &Inference, diagnostics: &mut Vec<Diagnostic>) {
+    walk(doc.tree.root_node(), &doc.source, inference, diagnostics);
+}
+
+fn walk(node: Node, source: &str, inference: &Inference, diagnostics: &mut Vec<Diagnostic>) {
+    if node.kind() == "method" {
+        if let Some(name_node) = node.child_by_field_name("name") {
+            let name = syntax::text(name_node, source);
+            let mut report = |severity, code: &str, message: String| {
+                diagnostics.push(Diagnostic {
+                    range: node_range(name_node),
+                    severity: Some(severity),
+                    code: Some(NumberOrString::String(code.into())),
+                    source: Some("rholang-lsp".into()),
+                    message,
+                    ..Default::default()
+                });
+            };
+            match lookup(name) {
+                None => report(
+                    DiagnosticSeverity::WARNING,
+                    "unknown-method",
+                    format!("Unknown method `.{name}`"),
+                ),
+                Some(method) => {
+                    let argc = method_args(node).len();
+                    if argc != method.params.len() {
+                        report(
+                            DiagnosticSeverity::ERROR,
+                            "method-arity",
+                            format!(
+                                "`.{}` takes {} argument(s), found {argc}",
+                                method.signature(),
+                                method.params.len()
+                            ),
+                        );
+                    }
+                    let receiver = node
+                        .child_by_field_name("receiver")
+                        .and_then(|r| inference.type_of(r));
+                    if let Some(ty) = receiver.filter(|t| !method.accepts(*t)) {
+                        report(
+                            DiagnosticSeverity::ERROR,
+                            "method-receiver",
+                            format!("`.{name}` is not defined on {ty}"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+    for child in named_children(node) {
+        walk(child, source, inference, diagnostics);
+    }
+}
@@ -0,0 +1,99 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::scope::{Binder, BinderKind, Scopes};
+use crate::syntax::{bind_source, is_bind, is_send, send_channel};
+
+/// How a use of a private name touches it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Send,
+    Receive,
+    /// Anything else (passed in a payload, dereferenced, compared): the name
+    /// leaves the code we can see and may be used by anyone.
+    Escape,
+}
+
+/// Classify a use of a name by its position.
+pub fn access(node: Node) -> Access {
+    let Some(parent) = node.parent() else {
+        return Access::Escape;
+    };
+    let is = |n: Option<Node>| n.map(|n| n.id()) == Some(node.id());
+    if is_send(parent) && is(send_channel(parent)) {
+        Access::Send
+    } else if (is_bind(parent) && is(bind_source(parent)))
+        || (parent.kind() == "contract" && is(parent.child_by_field_name("name")))
+    {
+        Access::Receive
+    } else {
+        Access::Escape
+    }
+}
+
+/// Whether a binder is a private name from `new` (not bound to a system URI).
+pub fn is_private(binder: &Binder) -> bool {
+    binder.kind == BinderKind::New
+        && !binder.node.parent().is_some_and(|decl| {
+            let mut cursor = decl.walk();
+            let has_uri = decl
+                .children(&mut cursor)
+                .any(|c| c.kind() == "uri_literal");
+            has_uri
+        })
+}
+
+/// Warn about receives on private names nobody sends on and sends nobody receives.
+pub fn check(scopes: &Scopes, uri: &Url, diagnostics: &mut Vec<Diagnostic>) {
+    for (index, binder) in scopes.binders.iter().enumerate() {
+        if !is_private(binder) {
+            continue;
+        }
+        let uses: Vec<(Node, Access)> = scopes
+            .uses_of(index)
+            .map(|u| (u.node, access(u.node)))
+            .collect();
+        if uses.iter().any(|(_, a)| *a == Access::Escape) {
+            continue;
+        }
+        let sends = uses.iter().filter(|(_, a)| *a == Access::Send).count();
+        let receives = uses.iter().filter(|(_, a)| *a == Access::Receive).count();
+        let (orphaned, message, code) = match (sends, receives) {
+            (0, r) if r > 0 => (
+                Access::Receive,
+                format!(
+                    "Receive on `{}` blocks forever: nothing sends on this private name",
+                    binder.name
+                ),
+                "orphaned-receive",
+            ),
+            (s, 0) if s > 0 => (
+                Access::Send,
+                format!(
+                    "Send on `{}` is never received: nothing listens on this private name",
+                    binder.name
+                ),
+                "orphaned-send",
+            ),
+            _ => continue,
+        };
+        for (node, _) in uses.iter().filter(|(_, a)| *a == orphaned) {
+            diagnostics.push(Diagnostic {
+                range: node_range(*node),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(code.into())),
+                source: Some("rholang-lsp".into()),
+                message: message.clone(),
+                related_information: Some(vec![DiagnosticRelatedInformation {
+                    location: Location {
+                        uri: uri.clone(),
+                        range: node_range(binder.node),
+                    },
+                    message: format!("`{}` declared here", binder.name),
+                }]),
+                ..Default::default()
+            });
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::scope::Scopes;
+use crate::syntax::{self, binary_parts, is_bind, named_children};
+
+/// The connective a node applies: `\/`, `/\` or `~`.
+fn connective(node: Node) -> Option<&'static str> {
+    if let Some((_, op @ ("\\/" | "/\\"), _)) = binary_parts(node) {
+        return Some(op);
+    }
+    let first = node.child(0)?;
+    (node.is_named() && !first.is_named() && first.kind() == "~").then_some("~")
+}
+
+/// Whether `child` is a pattern of `parent`: bind and `let` names, contract formals,
+/// `case` patterns and the right side of `matches`.
+fn is_pattern_of(child: Node, parent: Node) -> bool {
+    let field = |name: &str| {
+        parent
+            .child_by_field_name(name)
+            .is_some_and(|n| n.id() == child.id())
+    };
+    match parent.kind() {
+        "contract" => field("formals"),
+        "case" => field("pattern"),
+        "decl" => field("names"),
+        _ if is_bind(parent) => field("names"),
+        _ => binary_parts(parent)
+            .is_some_and(|(_, op, right)| op == "matches" && right.id() == child.id()),
+    }
+}
+
+/// Whether `node` sits inside a pattern.
+fn in_pattern(node: Node) -> bool {
+    let mut child = node;
+    while let Some(parent) = child.parent() {
+        if is_pattern_of(child, parent) {
+            return true;
+        }
+        child = parent;
+    }
+    false
+}
+
+/// Report connectives and `=x` outside patterns, and `=x` with no `x` in scope.
+pub fn check(doc: &Document, scopes: &Scopes, diagnostics: &mut Vec<Diagnostic>) {
+    walk(doc.tree.root_node(), &doc.source, scopes, diagnostics);
+}
+
+fn walk(node: Node, source: &str, scopes: &Scopes, diagnostics: &mut Vec<Diagnostic>) {
+    let mut report = |code: &str, message: String| {
+        diagnostics.push(Diagnostic {
+            range: node_range(node),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(code.into())),
+            source: Some("rholang-lsp".into()),
+            message,
+            ..Default::default()
+        });
+    };
+
+    if let Some(op) = connective(node) {
+        if !in_pattern(node) {
+            report(
+                "connective-placement",
+                format!("The connective `{op}` is only allowed in patterns"),
+            );
+        }
+    } else if node.kind() == "var_ref" {
+        let text = syntax::text(node, source);
+        if !in_pattern(node) {
+            report(
+                "connective-placement",
+                format!("`{text}` is only allowed in patterns"),
+            );
+        }
+        if let Some(var) = node.child_by_field_name("var") {
+            if scopes.resolve_use(var).is_none() {
+                report(
+                    "unbound-var-ref",
+                    format!(
+                        "`{text}` refers to `{}`, which is not bound here",
+                        syntax::text(var, source)
+                    ),
+                );
+            }
+        }
+    }
+    for child in named_children(node) {
+        walk(child, source, scopes, diagnostics);
+    }
+}
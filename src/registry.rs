@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::attach_fix;
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::syntax::named_children;
+use crate::system::uri_text;
+
+const PREFIX: &str = "rho:id:";
+const ZBASE32: &[u8; 32] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
+/// 32 hash bytes plus a 14-bit CRC, encoded as 270 bits of zbase32.
+const ID_BITS: usize = 270;
+const ID_LEN: usize = ID_BITS.div_ceil(5);
+
+/// Why a `rho:id:` URI cannot name a registry entry.
+#[derive(Debug, PartialEq, Eq)]
+pub enum IdError {
+    Length(usize),
+    Character(char),
+    Checksum { expected: u16, found: u16 },
+}
+
+impl fmt::Display for IdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IdError::Length(n) => write!(f, "expected {ID_LEN} zbase32 characters, found {n}"),
+            IdError::Character(c) => write!(f, "`{c}` is not a zbase32 character"),
+            IdError::Checksum { expected, found } => {
+                write!(
+                    f,
+                    "checksum mismatch (expected {expected:#06x}, found {found:#06x})"
+                )
+            }
+        }
+    }
+}
+
+/// CRC-14 over the hash, as computed by RNode when building registry URIs.
+fn crc14(bytes: &[u8]) -> u16 {
+    let mut rem: u16 = 0;
+    for &b in bytes {
+        rem ^= (b as u16) << 6;
+        for _ in 0..8 {
+            rem <<= 1;
+            if rem & 0x4000 != 0 {
+                rem ^= 0x4805;
+            }
+        }
+        rem &= 0x3fff;
+    }
+    rem
+}
+
+/// Decode the part after `rho:id:` into the 32-byte hash it names.
+pub fn decode_id(id: &str) -> Result<[u8; 32], IdError> {
+    let count = id.chars().count();
+    if count != ID_LEN {
+        return Err(IdError::Length(count));
+    }
+    let mut bytes = [0u8; 34];
+    let mut bit = 0;
+    for c in id.chars() {
+        let value = ZBASE32
+            .iter()
+            .position(|&z| z as char == c)
+            .ok_or(IdError::Character(c))?;
+        for shift in (0..5).rev() {
+            if bit < ID_BITS && (value >> shift) & 1 == 1 {
+                bytes[bit / 8] |= 0x80 >> (bit % 8);
+            }
+            bit += 1;
+        }
+    }
+
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&bytes[..32]);
+    // Low byte first, then the upper six bits shifted into the top of the last byte
+    let found = bytes[32] as u16 | ((bytes[33] as u16) >> 2) << 8;
+    let expected = crc14(&hash);
+    if found != expected {
+        return Err(IdError::Checksum { expected, found });
+    }
+    Ok(hash)
+}
+
+/// The registry id of a `rho:id:` URI literal, if it is one.
+fn id_of<'a>(node: Node, source: &'a str) -> Option<&'a str> {
+    if node.kind() != "uri_literal" {
+        return None;
+    }
+    uri_text(node, source).strip_prefix(PREFIX)
+}
+
+/// Well-formed registry ids used in a document.
+pub fn valid_ids(doc: &Document) -> Vec<String> {
+    let mut ids = Vec::new();
+    collect_ids(doc.tree.root_node(), &doc.source, &mut ids);
+    ids
+}
+
+/// Whether a document uses any of `ids`, or an invalid id one typo away from one,
+/// so that its registry diagnostics depend on them.
+pub fn mentions_any(doc: &Document, ids: &HashSet<String>) -> bool {
+    !ids.is_empty() && mentions(doc.tree.root_node(), &doc.source, ids)
+}
+
+fn mentions(node: Node, source: &str, ids: &HashSet<String>) -> bool {
+    if let Some(id) = id_of(node, source) {
+        let related = if decode_id(id).is_ok() {
+            ids.contains(id)
+        } else {
+            ids.iter().any(|known| one_typo(id, known))
+        };
+        if related {
+            return true;
+        }
+    }
+    named_children(node)
+        .into_iter()
+        .any(|child| mentions(child, source, ids))
+}
+
+fn collect_ids(node: Node, source: &str, ids: &mut Vec<String>) {
+    if let Some(id) = id_of(node, source) {
+        if decode_id(id).is_ok() && !ids.iter().any(|known| known == id) {
+            ids.push(id.to_string());
+        }
+    }
+    for child in named_children(node) {
+        collect_ids(child, source, ids);
+    }
+}
+
+/// Whether `a` becomes `b` with one substitution, insertion, deletion or swap of
+/// adjacent characters.
+fn one_typo(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (ra, rb) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    match (ra.len(), rb.len()) {
+        (1, 1) | (0, 1) | (1, 0) => true,
+        (2, 2) => ra[0] == rb[1] && ra[1] == rb[0],
+        _ => false,
+    }
+}
+
+/// Validate the encoding and checksum of `rho:id:` URIs. `known` holds valid ids
+/// from the other open documents, offered as replacements for ids one typo away.
+pub fn check(doc: &Document, known: &[String], diagnostics: &mut Vec<Diagnostic>) {
+    let mut candidates = valid_ids(doc);
+    for id in known {
+        if !candidates.contains(id) {
+            candidates.push(id.clone());
+        }
+    }
+    walk(doc.tree.root_node(), &doc.source, &candidates, diagnostics);
+}
+
+fn walk(node: Node, source: &str, candidates: &[String], diagnostics: &mut Vec<Diagnostic>) {
+    if let Some(id) = id_of(node, source) {
+        if let Err(error) = decode_id(id) {
+            let mut diagnostic = Diagnostic {
+                range: node_range(node),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String("invalid-registry-uri".into())),
+                source: Some("rholang-lsp".into()),
+                message: format!("Invalid registry URI: {error}"),
+                ..Default::default()
+            };
+            for candidate in candidates.iter().filter(|c| one_typo(id, c)) {
+                attach_fix(
+                    &mut diagnostic,
+                    format!("Replace with `{PREFIX}{candidate}`"),
+                    vec![TextEdit {
+                        range: node_range(node),
+                        new_text: format!("`{PREFIX}{candidate}`"),
+                    }],
+                );
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+    for child in named_children(node) {
+        walk(child, source, candidates, diagnostics);
+    }
+}
+
+/// Hover text for a `rho:id:` URI: the hash it names, or why it is malformed.
+pub fn hover_doc(doc: &Document, node: Node) -> Option<String> {
+    let id = id_of(node, &doc.source)?;
+    Some(match decode_id(id) {
+        Ok(hash) => {
+            let hex: String = hash.iter().map(|b| format!("{b:02x}")).collect();
+            format!("**registry id**, hash `{hex}`")
+        }
+        Err(error) => format!("**invalid registry id**: {error}"),
+    })
+}
@@ -0,0 +1,194 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::span_edit;
+use crate::document::Document;
+use crate::syntax::is_comment;
+
+const CLOSERS: &[&str] = &["}", ")", "]"];
+
+/// A fix inserting the token tree-sitter reports as missing. Closing delimiters go
+/// right after the last thing before them, on a line of their own when the construct
+/// spans several lines; other tokens keep a space on either side.
+pub fn missing_token(doc: &Document, node: Node) -> Option<(String, Vec<TextEdit>)> {
+    if node.is_named() {
+        return None;
+    }
+    let source = &doc.source;
+    let token = node.kind();
+    let title = format!("Insert `{token}`");
+    if CLOSERS.contains(&token) {
+        let at = node
+            .prev_sibling()
+            .map_or(node.start_byte(), |p| p.end_byte());
+        let parent = node.parent()?;
+        let text = if node_row(source, at) > parent.start_position().row {
+            format!("\n{}{token}", line_indent(source, parent.start_byte()))
+        } else {
+            token.to_string()
+        };
+        return Some((title, vec![span_edit(doc, at..at, text)]));
+    }
+
+    let at = node.start_byte();
+    let before = source[..at].chars().next_back();
+    let after = source[at..].chars().next();
+    let mut text = String::new();
+    if before.is_some_and(|c| !c.is_whitespace() && c != '(') && !matches!(token, "," | ";") {
+        text.push(' ');
+    }
+    text.push_str(token);
+    if after.is_some_and(|c| !c.is_whitespace()) {
+        text.push(' ');
+    }
+    Some((title, vec![span_edit(doc, at..at, text)]))
+}
+
+fn node_row(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count()
+}
+
+/// Leading whitespace of the line containing `offset`.
+fn line_indent(source: &str, offset: usize) -> &str {
+    let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = &source[start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Fixes for syntax errors that usually have one cause: `;` between processes where
+/// `|` composes them, `=` in a `for` where a bind needs `<-`, and a string missing its
+/// closing quote.
+pub fn error_fixes(doc: &Document, node: Node) -> Vec<(String, Vec<TextEdit>)> {
+    let source = &doc.source;
+    let mut fixes = Vec::new();
+
+    let mut cursor = node.walk();
+    let semicolons: Vec<Node> = node
+        .children(&mut cursor)
+        .filter(|c| c.kind() == ";")
+        .filter(|c| separates_processes(source, node, *c))
+        .collect();
+    if !semicolons.is_empty() && for_paren(source, node.start_byte()).is_none() {
+        let edits = semicolons
+            .iter()
+            .map(|s| span_edit(doc, s.byte_range(), "|".into()))
+            .collect();
+        fixes.push((
+            "Replace `;` with `|` to run processes in parallel".into(),
+            edits,
+        ));
+    }
+
+    if let Some(open) = for_paren(source, node.start_byte()) {
+        let span = &source[open..node.end_byte()];
+        if let Some(i) = lone_equals(span) {
+            let at = open + i;
+            fixes.push((
+                "Replace `=` with `<-` to receive in `for`".into(),
+                vec![span_edit(doc, at..at + 1, "<-".into())],
+            ));
+        }
+    }
+
+    if let Some(at) = unclosed_string(source, node.start_byte()) {
+        fixes.push((
+            "Close string literal".into(),
+            vec![span_edit(doc, at..at, "\"".into())],
+        ));
+    }
+    fixes
+}
+
+/// Whether a `;` sits between two complete processes, the first not a synchronous
+/// `!?` send (which a `;` rightly continues).
+fn separates_processes(source: &str, error: Node, semicolon: Node) -> bool {
+    let (Some(before), Some(after)) = (
+        neighbour(error, semicolon, false),
+        neighbour(error, semicolon, true),
+    ) else {
+        return false;
+    };
+    let complete = |n: Node| n.is_named() && !n.is_error() && !n.has_error() && !n.is_missing();
+    complete(before)
+        && complete(after)
+        && before.kind() != "send_sync"
+        && !source[before.start_byte()..semicolon.start_byte()].contains("!?")
+}
+
+/// The token next to `node` on one side, skipping comments, continuing past the
+/// `error` node that contains it.
+fn neighbour<'a>(error: Node<'a>, node: Node<'a>, forward: bool) -> Option<Node<'a>> {
+    let step = |n: Node<'a>| {
+        if forward {
+            n.next_sibling()
+        } else {
+            n.prev_sibling()
+        }
+    };
+    let mut current = node;
+    loop {
+        match step(current) {
+            Some(n) if is_comment(n) => current = n,
+            Some(n) => return Some(n),
+            None if current.parent().is_some_and(|p| p.id() == error.id()) => current = error,
+            None => return None,
+        }
+    }
+}
+
+/// The `(` of a `for` whose receipts are still open at `at`.
+fn for_paren(source: &str, at: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in source[..at].char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth > 0 => depth -= 1,
+            '(' => {
+                let before = source[..i].trim_end();
+                let is_for = before.ends_with("for")
+                    && !before[..before.len() - 3]
+                        .ends_with(|c: char| c.is_alphanumeric() || c == '_');
+                return is_for.then_some(i);
+            }
+            '{' | '}' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Offset of the first `=` in `text` that is not part of `==`, `<=`, `=>` or `!=`.
+fn lone_equals(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    (0..bytes.len()).find(|&i| {
+        bytes[i] == b'='
+            && !matches!(
+                i.checked_sub(1).map(|p| bytes[p]),
+                Some(b'=' | b'<' | b'>' | b'!')
+            )
+            && !matches!(bytes.get(i + 1), Some(b'=' | b'>'))
+    })
+}
+
+/// Where the closing quote belongs when the line at `offset` has an odd number of
+/// quotes: before the punctuation that ends the line, after the text of the string.
+fn unclosed_string(source: &str, offset: usize) -> Option<usize> {
+    let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i);
+    let line = &source[start..end];
+    let code = line.split("//").next().unwrap_or(line);
+    let quotes: Vec<usize> = code
+        .char_indices()
+        .filter(|&(i, c)| c == '"' && !code[..i].ends_with('\\'))
+        .map(|(i, _)| i)
+        .collect();
+    if quotes.len().is_multiple_of(2) {
+        return None;
+    }
+    let open = *quotes.last()?;
+    let rest = &code[open + 1..];
+    let content = rest.trim_end_matches(|c: char| c.is_whitespace() || ")]},;|".contains(c));
+    Some(start + open + 1 + content.len())
+}
@@ -0,0 +1,295 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::span_edit;
+use crate::declare::{wrap_in_new, INDENT};
+use crate::document::Document;
+use crate::extract::fresh_name;
+use crate::scope::{Channel, Scopes};
+use crate::signature::{collect_sends, paren_contents};
+use crate::syntax::{self, has_remainder, named_children, names_list, send_operator};
+
+/// Processes whose continuation is a sub-process, so an acknowledgement belongs at the
+/// end of that sub-process rather than beside them.
+const CONTROL: &[&str] = &[
+    "block",
+    "parenthesized",
+    "new",
+    "let",
+    "input",
+    "select",
+    "ifElse",
+    "match",
+];
+
+/// A contract gaining a return channel.
+pub struct Plan {
+    pub name: String,
+    /// Name of a contract on a free name, whose callers may live in other files;
+    /// `None` for private contracts.
+    pub free_name: Option<String>,
+}
+
+impl Plan {
+    pub fn title(&self) -> String {
+        format!("Add a return channel to `{}`", self.name)
+    }
+}
+
+/// For the contract whose header is under the cursor: append an `ack` formal, send on
+/// it where each path of the body ends, pass it along in recursive calls and make the
+/// callers in this document wait for it.
+pub fn plan(doc: &Document, range: Range) -> Option<(Plan, Vec<TextEdit>)> {
+    let source = &doc.source;
+    let offset = doc.offset_at(range.start.line as usize, range.start.character as usize);
+    let mut contract = doc
+        .tree
+        .root_node()
+        .named_descendant_for_byte_range(offset, offset)?;
+    while contract.kind() != "contract" {
+        contract = contract.parent()?;
+    }
+    let name = contract.child_by_field_name("name")?;
+    let body = contract.child_by_field_name("proc")?;
+    if offset >= body.start_byte()
+        || contract
+            .child_by_field_name("formals")
+            .is_some_and(has_remainder)
+    {
+        return None;
+    }
+    let scopes = Scopes::analyze(doc);
+    let channel = scopes.channel(name)?;
+    if acknowledges(&scopes, contract) {
+        return None;
+    }
+    let ack = fresh_name(doc, "ack");
+
+    let formals = paren_contents(contract, name.end_byte(), source)?;
+    let mut edits = vec![append_arg(doc, formals, &ack)];
+    let mut sends = Vec::new();
+    collect_sends(doc.tree.root_node(), &scopes, channel, &mut sends);
+    // `!?` already supplies a return channel of its own
+    if !sends.iter().all(|s| kind_send(*s)) {
+        return None;
+    }
+    for send in &sends {
+        if within(*send, body) {
+            // Recursive calls hand the caller's channel on
+            let args = paren_contents(*send, send_operator(*send, source)?.1.end, source)?;
+            edits.push(append_arg(doc, args, &format!("*{ack}")));
+        } else {
+            edits.push(call_site(doc, *send, &ack)?);
+        }
+    }
+    let mut terminal = Terminal {
+        doc,
+        scopes: &scopes,
+        channel,
+        ack: &ack,
+        edits: Vec::new(),
+    };
+    terminal.visit(body);
+    edits.extend(terminal.edits);
+
+    let plan = Plan {
+        name: syntax::text(name, source).to_string(),
+        free_name: match channel {
+            Channel::Free(n) => Some(n.to_string()),
+            Channel::Bound(_) => None,
+        },
+    };
+    Some((plan, edits))
+}
+
+/// Edits making the sends to a free-name contract in another document wait for the
+/// acknowledgement; `None` when the document calls it with `!?`, which the
+/// refactoring cannot extend.
+pub fn call_site_edits(doc: &Document, plan: &Plan) -> Option<Vec<TextEdit>> {
+    let Some(name) = &plan.free_name else {
+        return Some(Vec::new());
+    };
+    let scopes = Scopes::analyze(doc);
+    let mut sends = Vec::new();
+    collect_sends(
+        doc.tree.root_node(),
+        &scopes,
+        Channel::Free(name),
+        &mut sends,
+    );
+    if !sends.iter().all(|s| kind_send(*s)) {
+        return None;
+    }
+    let ack = fresh_name(doc, "ack");
+    Some(
+        sends
+            .into_iter()
+            .filter_map(|s| call_site(doc, s, &ack))
+            .collect(),
+    )
+}
+
+/// Whether the contract's last formal is already a return channel: a plain name the
+/// body sends `Nil` on.
+fn acknowledges(scopes: &Scopes, contract: Node) -> bool {
+    let Some(last) = contract
+        .child_by_field_name("formals")
+        .and_then(|f| names_list(f).pop())
+        .filter(|f| f.kind() == "var")
+    else {
+        return false;
+    };
+    let Some(binder) = scopes.binder_of(last) else {
+        return false;
+    };
+    scopes.uses_of(binder).any(|u| {
+        u.node
+            .parent()
+            .filter(|p| kind_send(*p))
+            .filter(|p| syntax::send_channel(*p).is_some_and(|c| c.id() == u.node.id()))
+            .is_some_and(|send| matches!(syntax::send_args(send)[..], [arg] if arg.kind() == "nil"))
+    })
+}
+
+fn kind_send(node: Node) -> bool {
+    node.kind() == "send"
+}
+
+fn within(inner: Node, outer: Node) -> bool {
+    outer.start_byte() <= inner.start_byte() && inner.end_byte() <= outer.end_byte()
+}
+
+/// Insert `arg` as the last entry of a parenthesized list.
+fn append_arg(doc: &Document, list: std::ops::Range<usize>, arg: &str) -> TextEdit {
+    let text = if doc.source[list.clone()].trim().is_empty() {
+        arg.to_string()
+    } else {
+        format!(", {arg}")
+    };
+    span_edit(doc, list.end..list.end, text)
+}
+
+/// `c!(x)` becomes `new ack in { c!(x, *ack) | for (_ <- ack) { Nil } }`, leaving the
+/// `for` body as the place to continue once the contract is done.
+fn call_site(doc: &Document, send: Node, ack: &str) -> Option<TextEdit> {
+    let source = &doc.source;
+    let args = paren_contents(send, send_operator(send, source)?.1.end, source)?;
+    let separator = if source[args.clone()].trim().is_empty() {
+        ""
+    } else {
+        ", "
+    };
+    let call = format!(
+        "{}{separator}*{ack}{}",
+        &source[send.start_byte()..args.end],
+        &source[args.end..send.end_byte()]
+    );
+    let body = format!("{call} |\nfor (_ <- {ack}) {{\n{INDENT}Nil\n}}");
+    Some(wrap_in_new(send, ack, &body))
+}
+
+/// Collects the edits sending on the return channel where each path of a body ends.
+struct Terminal<'a, 'd> {
+    doc: &'d Document,
+    scopes: &'d Scopes<'a>,
+    channel: Channel<'a>,
+    ack: &'d str,
+    edits: Vec<TextEdit>,
+}
+
+impl Terminal<'_, '_> {
+    fn visit(&mut self, node: Node) {
+        let field = |name: &str| node.child_by_field_name(name);
+        match node.kind() {
+            "block" | "parenthesized" => match named_children(node)[..] {
+                [inner] => self.visit(inner),
+                _ => self.leaf(node),
+            },
+            "new" | "input" => {
+                if let Some(proc) = field("proc") {
+                    self.visit(proc);
+                }
+            }
+            "let" => {
+                if let Some(proc) = field("proc").or_else(|| field("body")) {
+                    self.visit(proc);
+                }
+            }
+            "ifElse" => {
+                if let Some(then) = field("ifTrue") {
+                    self.visit(then);
+                }
+                match field("alternative") {
+                    Some(alternative) => self.visit(alternative),
+                    None => self.insert(node.end_byte(), format!(" else {{ {}!(Nil) }}", self.ack)),
+                }
+            }
+            "match" | "select" => {
+                let mut arms = Vec::new();
+                collect_arms(node, &mut arms);
+                for arm in arms {
+                    if let Some(proc) = arm.child_by_field_name("proc") {
+                        self.visit(proc);
+                    }
+                }
+            }
+            "par" => {
+                let children = named_children(node);
+                let control: Vec<Node> = children
+                    .iter()
+                    .copied()
+                    .filter(|c| {
+                        CONTROL.contains(&c.kind()) || c.kind() == "par" || self.recursive(*c)
+                    })
+                    .collect();
+                match control[..] {
+                    // Siblings of the one continuing process run alongside it
+                    [continuing] => self.visit(continuing),
+                    _ => self.leaf(node),
+                }
+            }
+            "nil" => self.edits.push(span_edit(
+                self.doc,
+                node.byte_range(),
+                format!("{}!(Nil)", self.ack),
+            )),
+            _ if self.recursive(node) => {}
+            _ => self.leaf(node),
+        }
+    }
+
+    /// A call of the contract itself, which now passes the channel on.
+    fn recursive(&self, node: Node) -> bool {
+        kind_send(node)
+            && syntax::send_channel(node).and_then(|c| self.scopes.channel(c)) == Some(self.channel)
+    }
+
+    /// Acknowledge alongside a process with no continuation of its own.
+    fn leaf(&mut self, node: Node) {
+        let ack = format!("{}!(Nil)", self.ack);
+        let in_par = node
+            .parent()
+            .is_some_and(|p| matches!(p.kind(), "par" | "block" | "source_file"));
+        if in_par {
+            self.insert(node.end_byte(), format!(" | {ack}"));
+        } else {
+            self.insert(node.start_byte(), "{ ".into());
+            self.insert(node.end_byte(), format!(" | {ack} }}"));
+        }
+    }
+
+    fn insert(&mut self, at: usize, text: String) {
+        self.edits.push(span_edit(self.doc, at..at, text));
+    }
+}
+
+/// The `case`s of a `match` and the `branch`es of a `select`.
+fn collect_arms<'a>(node: Node<'a>, arms: &mut Vec<Node<'a>>) {
+    for child in named_children(node) {
+        if matches!(child.kind(), "case" | "branch") {
+            arms.push(child);
+        } else if matches!(child.kind(), "cases" | "branches") {
+            collect_arms(child, arms);
+        }
+    }
+}
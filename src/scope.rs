@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+use tree_sitter::Node;
+
+use crate::document::Document;
+use crate::syntax::{self, bind_source, branch_binds, let_decls, named_children, receipt_groups};
+
+/// Whether a variable stands for a name (channel) or a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    /// `x` in `new x`, `contract c(x)`, `for (x <- ch)`
+    Name,
+    /// `x` in `for (@x <- ch)` or `match P { x => ... }`
+    Process,
+}
+
+/// What introduced a binding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinderKind {
+    /// `new x in { ... }`
+    New,
+    /// `contract c(x) = { ... }`
+    Formal,
+    /// `for (x <- ch) { ... }` and `select` branches
+    Receive,
+    /// `let x = P in { ... }`
+    Let,
+    /// `match P { x => ... }`
+    Case,
+}
+
+pub struct Binder<'a> {
+    pub name: &'a str,
+    /// The `var` node that introduces the name.
+    pub node: Node<'a>,
+    pub sort: Sort,
+    pub kind: BinderKind,
+    /// Index of the outer binder with the same name, if this one hides it.
+    pub shadows: Option<usize>,
+}
+
+pub struct Use<'a> {
+    pub node: Node<'a>,
+    /// Index of the binder this use resolves to, `None` when the name is free.
+    pub binder: Option<usize>,
+}
+
+/// How a channel is identified: by its binder, or by its text when free.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel<'a> {
+    Bound(usize),
+    Free(&'a str),
+}
+
+/// Binders and resolved uses for a whole document.
+pub struct Scopes<'a> {
+    pub binders: Vec<Binder<'a>>,
+    pub uses: Vec<Use<'a>>,
+    source: &'a str,
+    /// Binder index by the id of its `var` node.
+    binder_ids: HashMap<usize, usize>,
+    /// Use index by the id of its `var` node.
+    use_ids: HashMap<usize, usize>,
+    /// Use indices per binder.
+    uses_by_binder: Vec<Vec<usize>>,
+}
+
+impl<'a> Scopes<'a> {
+    pub fn analyze(doc: &'a Document) -> Self {
+        let mut scopes = Scopes {
+            binders: Vec::new(),
+            uses: Vec::new(),
+            source: &doc.source,
+            binder_ids: HashMap::new(),
+            use_ids: HashMap::new(),
+            uses_by_binder: Vec::new(),
+        };
+        let mut env = Vec::new();
+        scopes.walk(doc.tree.root_node(), &mut env);
+        scopes.index();
+        scopes
+    }
+
+    /// Build the lookup tables; the first entry for a node wins, as a scan would find it.
+    fn index(&mut self) {
+        for (i, binder) in self.binders.iter().enumerate() {
+            self.binder_ids.entry(binder.node.id()).or_insert(i);
+        }
+        self.uses_by_binder = vec![Vec::new(); self.binders.len()];
+        for (i, u) in self.uses.iter().enumerate() {
+            self.use_ids.entry(u.node.id()).or_insert(i);
+            if let Some(b) = u.binder {
+                self.uses_by_binder[b].push(i);
+            }
+        }
+    }
+
+    /// The binder a `var` node refers to, `None` if it is free or not a use.
+    pub fn resolve_use(&self, node: Node) -> Option<usize> {
+        self.use_ids
+            .get(&node.id())
+            .and_then(|&i| self.uses[i].binder)
+    }
+
+    /// Uses resolving to the given binder.
+    pub fn uses_of(&self, binder: usize) -> impl Iterator<Item = &Use<'a>> {
+        self.uses_by_binder
+            .get(binder)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.uses[i])
+    }
+
+    /// The binder a `var` node introduces or refers to.
+    pub fn binder_of(&self, node: Node) -> Option<usize> {
+        self.binder_ids
+            .get(&node.id())
+            .copied()
+            .or_else(|| self.resolve_use(node))
+    }
+
+    /// The process a `let` binds to the binder of `var` (`P` in `let x = P`), for
+    /// binders written as `x` or `@x`.
+    pub fn let_value(&self, var: Node) -> Option<Node<'a>> {
+        let binder = &self.binders[self.binder_of(var)?];
+        if binder.kind != BinderKind::Let {
+            return None;
+        }
+        let mut decl = binder.node.parent()?;
+        while decl.kind() != "decl" {
+            decl = decl.parent()?;
+        }
+        let names = syntax::names_list(decl.child_by_field_name("names")?);
+        let index = names
+            .iter()
+            .position(|n| syntax::pattern_var(*n).is_some_and(|v| v.id() == binder.node.id()))?;
+        syntax::decl_values(decl).get(index).copied()
+    }
+
+    /// The binder a use of `name` at `node` would resolve to, `None` if it would be free.
+    pub fn visible_at(&self, name: &str, node: Node) -> Option<usize> {
+        self.binders
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.name == name)
+            .filter_map(|(i, b)| Some((i, scope_body(b.node)?)))
+            .filter(|(_, body)| {
+                body.start_byte() <= node.start_byte() && node.end_byte() <= body.end_byte()
+            })
+            .max_by_key(|(i, body)| (body.start_byte(), std::cmp::Reverse(body.end_byte()), *i))
+            .map(|(i, _)| i)
+    }
+
+    /// Identify a channel written as a plain `var`.
+    pub fn channel(&self, node: Node) -> Option<Channel<'a>> {
+        if node.kind() != "var" {
+            return None;
+        }
+        Some(match self.resolve_use(node) {
+            Some(binder) => Channel::Bound(binder),
+            None => Channel::Free(syntax::text(node, self.source)),
+        })
+    }
+
+    fn walk(&mut self, node: Node<'a>, env: &mut Vec<usize>) {
+        match node.kind() {
+            "var" => self.resolve(node, env),
+            "new" => {
+                let mark = env.len();
+                if let Some(decls) = node.child_by_field_name("decls") {
+                    for decl in named_children(decls) {
+                        if decl.kind() != "name_decl" {
+                            continue;
+                        }
+                        if let Some(var) = decl.named_child(0).filter(|n| n.kind() == "var") {
+                            self.bind(var, Sort::Name, BinderKind::New, env);
+                        }
+                    }
+                }
+                if let Some(proc) = node.child_by_field_name("proc") {
+                    self.walk(proc, env);
+                }
+                env.truncate(mark);
+            }
+            "contract" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    self.walk(name, env);
+                }
+                let mark = env.len();
+                if let Some(formals) = node.child_by_field_name("formals") {
+                    self.bind_pattern(formals, Sort::Name, BinderKind::Formal, env);
+                }
+                if let Some(proc) = node.child_by_field_name("proc") {
+                    self.walk(proc, env);
+                }
+                env.truncate(mark);
+            }
+            "input" => {
+                let mark = env.len();
+                if let Some(receipts) = node.child_by_field_name("receipts") {
+                    for group in receipt_groups(receipts) {
+                        self.walk_binds(&group, env);
+                    }
+                }
+                if let Some(proc) = node.child_by_field_name("proc") {
+                    self.walk(proc, env);
+                }
+                env.truncate(mark);
+            }
+            "let" => {
+                let mark = env.len();
+                if let Some(decls) = node.child_by_field_name("decls") {
+                    for decl in let_decls(decls) {
+                        for value in syntax::decl_values(decl) {
+                            self.walk(value, env);
+                        }
+                        if let Some(names) = decl.child_by_field_name("names") {
+                            self.bind_pattern(names, Sort::Name, BinderKind::Let, env);
+                        }
+                    }
+                }
+                if let Some(body) = node
+                    .child_by_field_name("proc")
+                    .or_else(|| node.child_by_field_name("body"))
+                {
+                    self.walk(body, env);
+                }
+                env.truncate(mark);
+            }
+            "case" => {
+                let mark = env.len();
+                if let Some(pattern) = node.child_by_field_name("pattern") {
+                    self.bind_pattern(pattern, Sort::Process, BinderKind::Case, env);
+                }
+                if let Some(proc) = node.child_by_field_name("proc") {
+                    self.walk(proc, env);
+                }
+                env.truncate(mark);
+            }
+            "branch" => {
+                let mark = env.len();
+                let binds = branch_binds(node);
+                self.walk_binds(&binds, env);
+                if let Some(proc) = node.child_by_field_name("proc") {
+                    self.walk(proc, env);
+                }
+                env.truncate(mark);
+            }
+            "method" => {
+                // The method name is not a reference to a binding
+                let name = node.child_by_field_name("name").map(|n| n.id());
+                for child in named_children(node) {
+                    if Some(child.id()) != name {
+                        self.walk(child, env);
+                    }
+                }
+            }
+            _ => {
+                for child in named_children(node) {
+                    self.walk(child, env);
+                }
+            }
+        }
+    }
+
+    /// Channels of a group of binds are resolved before any of the group's names are bound.
+    fn walk_binds(&mut self, binds: &[Node<'a>], env: &mut Vec<usize>) {
+        for bind in binds {
+            if let Some(source) = bind_source(*bind) {
+                self.walk(source, env);
+            }
+        }
+        for bind in binds {
+            if let Some(names) = bind.child_by_field_name("names") {
+                self.bind_pattern(names, Sort::Name, BinderKind::Receive, env);
+            }
+        }
+    }
+
+    /// Every free `var` in a pattern is a binder; `=x` refers to an existing binding.
+    /// `sort` is the position the pattern occupies: `@` switches to process, `*` to name.
+    fn bind_pattern(&mut self, node: Node<'a>, sort: Sort, kind: BinderKind, env: &mut Vec<usize>) {
+        let sort = match node.kind() {
+            "quote" => Sort::Process,
+            "eval" => Sort::Name,
+            _ => sort,
+        };
+        match node.kind() {
+            "var" => self.bind(node, sort, kind, env),
+            "var_ref" => {
+                if let Some(var) = node.child_by_field_name("var") {
+                    self.resolve(var, env);
+                }
+            }
+            _ => {
+                for child in named_children(node) {
+                    self.bind_pattern(child, sort, kind, env);
+                }
+            }
+        }
+    }
+
+    fn bind(&mut self, var: Node<'a>, sort: Sort, kind: BinderKind, env: &mut Vec<usize>) {
+        let name = syntax::text(var, self.source);
+        let shadows = self.lookup(name, env);
+        self.binders.push(Binder {
+            name,
+            node: var,
+            sort,
+            kind,
+            shadows,
+        });
+        env.push(self.binders.len() - 1);
+    }
+
+    fn resolve(&mut self, var: Node<'a>, env: &[usize]) {
+        let name = syntax::text(var, self.source);
+        let binder = self.lookup(name, env);
+        self.uses.push(Use { node: var, binder });
+    }
+
+    fn lookup(&self, name: &str, env: &[usize]) -> Option<usize> {
+        env.iter()
+            .rev()
+            .copied()
+            .find(|&i| self.binders[i].name == name)
+    }
+}
+
+/// The process a binder's name is in scope for: the body of the construct declaring it.
+fn scope_body(binder: Node) -> Option<Node> {
+    let mut node = binder.parent()?;
+    while !matches!(
+        node.kind(),
+        "new" | "contract" | "input" | "let" | "case" | "branch"
+    ) {
+        node = node.parent()?;
+    }
+    node.child_by_field_name("proc")
+        .or_else(|| node.child_by_field_name("body"))
+}
@@ -2,6 +2,7 @@ use tower_lsp::lsp_types::*;
 use tree_sitter::Node;
 
 use crate::document::Document;
+use crate::interpolation;
 
 // Token type indices — must match LEGEND_TYPE order
 const TT_KEYWORD: u32 = 0;
@@ -107,6 +108,25 @@ fn collect_tokens(node: Node, source: &str, tokens: &mut Vec<(u32, u32, u32, u32
         }
 
         // Literals
+        "string_literal" if start.row == end.row && interpolation::is_template(node) => {
+            // Split the template around `${key}` placeholders
+            let base = node.start_byte();
+            let mut from = base;
+            for p in interpolation::placeholders(node, source) {
+                let at = |byte: usize| col + (byte - base) as u32;
+                if p.start > from {
+                    tokens.push((line, at(from), (p.start - from) as u32, TT_STRING));
+                }
+                tokens.push((line, at(p.start), 2, TT_OPERATOR));
+                tokens.push((line, at(p.start + 2), p.key.len() as u32, TT_VARIABLE));
+                tokens.push((line, at(p.end - 1), 1, TT_OPERATOR));
+                from = p.end;
+            }
+            if node.end_byte() > from {
+                let len = (node.end_byte() - from) as u32;
+                tokens.push((line, col + (from - base) as u32, len, TT_STRING));
+            }
+        }
         "string_literal" | "uri_literal" => {
             tokens.push((line, col, len, TT_STRING));
         }
@@ -0,0 +1,32 @@
+use tower_lsp::lsp_types::*;
+
+use crate::diagnostics::node_range;
+use crate::scope::Scopes;
+
+/// Warn when a binder hides an outer binder of the same name, e.g. a nested
+/// `for (ret <- ch)` inside `contract foo(ret)`.
+pub fn check(scopes: &Scopes, uri: &Url, diagnostics: &mut Vec<Diagnostic>) {
+    for binder in &scopes.binders {
+        let Some(outer) = binder.shadows.map(|i| &scopes.binders[i]) else {
+            continue;
+        };
+        diagnostics.push(Diagnostic {
+            range: node_range(binder.node),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("shadowing".into())),
+            source: Some("rholang-lsp".into()),
+            message: format!(
+                "`{}` shadows an outer binding of the same name",
+                binder.name
+            ),
+            related_information: Some(vec![DiagnosticRelatedInformation {
+                location: Location {
+                    uri: uri.clone(),
+                    range: node_range(outer.node),
+                },
+                message: format!("shadowed `{}` declared here", outer.name),
+            }]),
+            ..Default::default()
+        });
+    }
+}
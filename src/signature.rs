@@ -0,0 +1,196 @@
+use std::ops::Range as Span;
+
+use serde_json::Value;
+use tower_lsp::lsp_types::*;
+use tree_sitter::{Node, Point};
+
+use crate::code_actions::span_edit;
+use crate::document::Document;
+use crate::scope::{Channel, Scopes};
+use crate::syntax::{self, has_remainder, is_send, named_children, names_list, send_operator};
+
+/// Command applying a new formal list to a contract and all sends to it.
+pub const CHANGE_SIGNATURE: &str = "rholang.applyChangeSignature";
+
+/// Arguments of [`CHANGE_SIGNATURE`]: the contract at `position` in `uri` gets
+/// `formals`. Formals spelled like an existing one keep its arguments; others are
+/// new and receive `Nil` at every call site.
+pub struct ChangeSignature {
+    pub uri: Url,
+    pub position: Position,
+    pub formals: Vec<String>,
+}
+
+impl ChangeSignature {
+    pub fn from_json(value: &Value) -> Option<Self> {
+        Some(ChangeSignature {
+            uri: Url::parse(value.get("uri")?.as_str()?).ok()?,
+            position: serde_json::from_value(value.get("position")?.clone()).ok()?,
+            formals: value
+                .get("formals")?
+                .as_array()?
+                .iter()
+                .map(|f| f.as_str().map(|s| s.trim().to_string()))
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+/// The contract being changed: which sends reach it and where each new argument
+/// comes from.
+pub struct Plan {
+    /// Name of a contract on a free name, whose callers may live in other files;
+    /// `None` for private contracts.
+    pub free_name: Option<String>,
+    /// For each new formal, the index of the old argument it takes over.
+    mapping: Vec<Option<usize>>,
+}
+
+/// Byte range strictly inside the parentheses following `after`.
+pub fn paren_contents(node: Node, after: usize, source: &str) -> Option<Span<usize>> {
+    let open = after + source[after..node.end_byte()].find('(')?;
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in source[open..node.end_byte()].char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + 1..open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Plan the change for the contract at `position` and produce the edits for its own
+/// document: the formal list and the sends in that file. Fails when a formal left out
+/// of the new list is still used in the body, which would leave the use dangling.
+pub fn plan(doc: &Document, change: &ChangeSignature) -> Result<(Plan, Vec<TextEdit>), String> {
+    let source = &doc.source;
+    let not_found = || "No contract with a fixed formal list at this position".to_string();
+    let contract = contract_at(doc, change.position).ok_or_else(not_found)?;
+    let name = contract.child_by_field_name("name").ok_or_else(not_found)?;
+    let formals = contract.child_by_field_name("formals");
+    if formals.is_some_and(has_remainder) {
+        return Err(not_found());
+    }
+    let old_formals = formals.map(names_list).unwrap_or_default();
+    let old: Vec<&str> = old_formals
+        .iter()
+        .map(|f| syntax::text(*f, source))
+        .collect();
+    let mapping = change
+        .formals
+        .iter()
+        .map(|f| old.iter().position(|o| o == f))
+        .collect();
+
+    let scopes = Scopes::analyze(doc);
+    let still_used: Vec<String> = old_formals
+        .iter()
+        .filter(|f| {
+            !change
+                .formals
+                .iter()
+                .any(|n| n == syntax::text(**f, source))
+        })
+        .flat_map(|f| {
+            scopes.binders.iter().enumerate().filter(move |(_, b)| {
+                f.start_byte() <= b.node.start_byte() && b.node.end_byte() <= f.end_byte()
+            })
+        })
+        .filter(|(i, _)| scopes.uses_of(*i).next().is_some())
+        .map(|(_, b)| format!("`{}`", b.name))
+        .collect();
+    if !still_used.is_empty() {
+        return Err(format!(
+            "{} still used in the contract body; keep the formal or remove its uses first",
+            still_used.join(", ")
+        ));
+    }
+
+    let channel = scopes.channel(name).ok_or_else(not_found)?;
+    let plan = Plan {
+        free_name: match channel {
+            Channel::Free(n) => Some(n.to_string()),
+            Channel::Bound(_) => None,
+        },
+        mapping,
+    };
+    let formals_span = paren_contents(contract, name.end_byte(), source).ok_or_else(not_found)?;
+    let mut edits = vec![span_edit(doc, formals_span, change.formals.join(", "))];
+    edits.extend(send_edits(doc, &scopes, channel, &plan));
+    Ok((plan, edits))
+}
+
+fn contract_at<'a>(doc: &'a Document, position: Position) -> Option<Node<'a>> {
+    let point = Point {
+        row: position.line as usize,
+        column: position.character as usize,
+    };
+    let mut contract = doc
+        .tree
+        .root_node()
+        .named_descendant_for_point_range(point, point)?;
+    while contract.kind() != "contract" {
+        contract = contract.parent()?;
+    }
+    Some(contract)
+}
+
+/// Edits updating the sends to a free-name contract in another document.
+pub fn call_site_edits(doc: &Document, plan: &Plan) -> Vec<TextEdit> {
+    let Some(name) = &plan.free_name else {
+        return Vec::new();
+    };
+    let scopes = Scopes::analyze(doc);
+    send_edits(doc, &scopes, Channel::Free(name), plan)
+}
+
+fn send_edits(doc: &Document, scopes: &Scopes, channel: Channel, plan: &Plan) -> Vec<TextEdit> {
+    let mut sends = Vec::new();
+    collect_sends(doc.tree.root_node(), scopes, channel, &mut sends);
+    sends
+        .into_iter()
+        .filter_map(|send| {
+            let (_, op) = send_operator(send, &doc.source)?;
+            let span = paren_contents(send, op.end, &doc.source)?;
+            let args = syntax::send_args(send);
+            let new_args: Vec<&str> = plan
+                .mapping
+                .iter()
+                .map(|m| {
+                    m.and_then(|i| args.get(i))
+                        .map_or("Nil", |a| syntax::text(*a, &doc.source))
+                })
+                .collect();
+            Some(span_edit(doc, span, new_args.join(", ")))
+        })
+        .collect()
+}
+
+pub fn collect_sends<'a>(
+    node: Node<'a>,
+    scopes: &Scopes,
+    channel: Channel,
+    out: &mut Vec<Node<'a>>,
+) {
+    if is_send(node) && syntax::send_channel(node).and_then(|c| scopes.channel(c)) == Some(channel)
+    {
+        out.push(node);
+    }
+    for child in named_children(node) {
+        collect_sends(child, scopes, channel, out);
+    }
+}
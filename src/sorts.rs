@@ -0,0 +1,130 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::attach_fix;
+use crate::diagnostics::node_range;
+use crate::scope::{Scopes, Sort};
+use crate::syntax::{binary_parts, bind_source, is_bind, is_send, send_channel};
+
+/// Parents in which a `var` stands for a process.
+const PROCESS_CONTEXTS: &[&str] = &[
+    "source_file",
+    "block",
+    "par",
+    "parenthesized",
+    "inputs",
+    "args",
+    "list",
+    "set",
+    "tuple",
+    "key_value_pair",
+    "decl",
+    "ifElse",
+    "match",
+    "case",
+    "not",
+    "neg",
+];
+
+/// The sort a `var` must have where it appears, if its position determines one.
+pub fn expected_sort(node: Node, parent: Node) -> Option<Sort> {
+    let is = |n: Option<Node>| n.map(|n| n.id()) == Some(node.id());
+    match parent.kind() {
+        "eval" => Some(Sort::Name),
+        "quote" => Some(Sort::Process),
+        "var_ref" => None,
+        "contract" if is(parent.child_by_field_name("name")) => Some(Sort::Name),
+        "method" if is(parent.child_by_field_name("name")) => None,
+        "method" => Some(Sort::Process),
+        _ if is_send(parent) && is(send_channel(parent)) => Some(Sort::Name),
+        _ if is_bind(parent) && is(bind_source(parent)) => Some(Sort::Name),
+        kind if PROCESS_CONTEXTS.contains(&kind) || binary_parts(parent).is_some() => {
+            Some(Sort::Process)
+        }
+        _ => None,
+    }
+}
+
+/// Report names used where a process is required and vice versa, with fixes that
+/// insert or remove `@` / `*`.
+pub fn check(scopes: &Scopes, uri: &Url, diagnostics: &mut Vec<Diagnostic>) {
+    for u in &scopes.uses {
+        let Some(binder) = u.binder.map(|b| &scopes.binders[b]) else {
+            continue;
+        };
+        let Some(parent) = u.node.parent() else {
+            continue;
+        };
+        let Some(expected) = expected_sort(u.node, parent) else {
+            continue;
+        };
+        if expected == binder.sort {
+            continue;
+        }
+
+        let name = binder.name;
+        let at_var = Range {
+            start: node_range(u.node).start,
+            end: node_range(u.node).start,
+        };
+        let before_var = Range {
+            start: node_range(parent).start,
+            end: node_range(u.node).start,
+        };
+        let (message, title, edit) = match (expected, parent.kind()) {
+            (Sort::Name, "eval") => (
+                format!("`{name}` is bound to a process; `*` can only dereference a name"),
+                format!("Remove `*` from `{name}`"),
+                TextEdit {
+                    range: before_var,
+                    new_text: String::new(),
+                },
+            ),
+            (Sort::Name, _) => (
+                format!("`{name}` is bound to a process but used as a name; quote it as `@{name}`"),
+                format!("Insert `@` before `{name}`"),
+                TextEdit {
+                    range: at_var,
+                    new_text: "@".into(),
+                },
+            ),
+            (Sort::Process, "quote") => (
+                format!("`{name}` is already a name; `@` can only quote a process"),
+                format!("Remove `@` from `{name}`"),
+                TextEdit {
+                    range: before_var,
+                    new_text: String::new(),
+                },
+            ),
+            (Sort::Process, _) => (
+                format!("`{name}` is a name but used as a process; dereference it as `*{name}`"),
+                format!("Insert `*` before `{name}`"),
+                TextEdit {
+                    range: at_var,
+                    new_text: "*".into(),
+                },
+            ),
+        };
+
+        let mut diagnostic = Diagnostic {
+            range: node_range(u.node),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String("sort-mismatch".into())),
+            source: Some("rholang-lsp".into()),
+            message,
+            related_information: Some(vec![DiagnosticRelatedInformation {
+                location: Location {
+                    uri: uri.clone(),
+                    range: node_range(binder.node),
+                },
+                message: match binder.sort {
+                    Sort::Name => format!("`{name}` bound as a name here"),
+                    Sort::Process => format!("`{name}` bound as a process here"),
+                },
+            }]),
+            ..Default::default()
+        };
+        attach_fix(&mut diagnostic, title, vec![edit]);
+        diagnostics.push(diagnostic);
+    }
+}
@@ -0,0 +1,188 @@
+use tree_sitter::Node;
+
+/// Whether a node is a comment (comments are extras and may appear anywhere).
+pub fn is_comment(node: Node) -> bool {
+    matches!(node.kind(), "line_comment" | "block_comment")
+}
+
+/// Named children of a node, skipping comments.
+pub fn named_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|n| !is_comment(*n))
+        .collect()
+}
+
+/// Source text of a node.
+pub fn text<'a>(node: Node, source: &'a str) -> &'a str {
+    node.utf8_text(source.as_bytes()).unwrap_or("")
+}
+
+/// The individual binds of a `receipts` node, grouped by receipt.
+///
+/// Receipts are separated by `;` (sequential), binds within a receipt by `&` (join).
+pub fn receipt_groups(receipts: Node) -> Vec<Vec<Node>> {
+    named_children(receipts)
+        .into_iter()
+        .map(|receipt| {
+            if is_bind(receipt) {
+                vec![receipt]
+            } else {
+                named_children(receipt)
+                    .into_iter()
+                    .filter(|n| is_bind(*n))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// The binds of a `select` branch pattern (`a <- x & b <- y`).
+pub fn branch_binds(branch: Node) -> Vec<Node> {
+    let mut cursor = branch.walk();
+    let binds = branch
+        .children_by_field_name("pattern", &mut cursor)
+        .flat_map(|p| {
+            if is_bind(p) {
+                vec![p]
+            } else {
+                named_children(p)
+            }
+        })
+        .filter(|n| is_bind(*n))
+        .collect();
+    binds
+}
+
+pub fn is_bind(node: Node) -> bool {
+    matches!(node.kind(), "linear_bind" | "repeated_bind" | "peek_bind")
+}
+
+/// The channel a bind receives on (`ch` in `x <- ch`).
+pub fn bind_source(bind: Node) -> Option<Node> {
+    if let Some(input) = bind.child_by_field_name("input") {
+        return Some(input);
+    }
+    let names = bind.child_by_field_name("names").map(|n| n.id());
+    named_children(bind)
+        .into_iter()
+        .rev()
+        .find(|n| Some(n.id()) != names)
+}
+
+/// The `decl` nodes of a `let`, whether separated by `;` or `&`.
+pub fn let_decls(decls: Node) -> Vec<Node> {
+    if decls.kind() == "decl" {
+        return vec![decls];
+    }
+    named_children(decls)
+        .into_iter()
+        .flat_map(let_decls)
+        .collect()
+}
+
+/// The values a `decl` binds, in order (`P, Q` in `x, y = P, Q`).
+pub fn decl_values(decl: Node) -> Vec<Node> {
+    let names = decl.child_by_field_name("names").map(|n| n.id());
+    named_children(decl)
+        .into_iter()
+        .filter(|n| Some(n.id()) != names)
+        .collect()
+}
+
+pub fn is_send(node: Node) -> bool {
+    matches!(node.kind(), "send" | "send_sync")
+}
+
+/// The channel a send targets (`ch` in `ch!(P)`).
+pub fn send_channel(send: Node) -> Option<Node> {
+    send.child_by_field_name("channel")
+        .or_else(|| send.named_child(0))
+}
+
+/// The send operator (`!`, `!!` or `!?`) and its byte range, taken from the text
+/// between the channel and the argument list.
+pub fn send_operator<'a>(send: Node, source: &'a str) -> Option<(&'a str, std::ops::Range<usize>)> {
+    let after = send_channel(send)?.end_byte();
+    let paren = after + source[after..send.end_byte()].find('(')?;
+    let between = &source[after..paren];
+    let start = after + (between.len() - between.trim_start().len());
+    let op = between.trim();
+    Some((op, start..start + op.len()))
+}
+
+/// The processes sent by a send, in order.
+pub fn send_args(send: Node) -> Vec<Node> {
+    send.child_by_field_name("inputs")
+        .map(named_children)
+        .unwrap_or_default()
+}
+
+/// The names in a `names` list, excluding any `...@rest` remainder.
+pub fn names_list(names: Node) -> Vec<Node> {
+    let mut cursor = names.walk();
+    let list = names
+        .children(&mut cursor)
+        .take_while(|n| n.kind() != "..." && !is_remainder(*n))
+        .filter(|n| n.is_named() && !is_comment(*n))
+        .collect();
+    list
+}
+
+/// Whether a `names` list ends with a `...@rest` remainder, accepting any arity above its length.
+pub fn has_remainder(names: Node) -> bool {
+    let mut cursor = names.walk();
+    let found = names
+        .children(&mut cursor)
+        .any(|n| n.kind() == "..." || is_remainder(n));
+    found
+}
+
+fn is_remainder(node: Node) -> bool {
+    node.kind().contains("remainder")
+}
+
+/// The variable a name pattern binds directly: `x` or `@x`.
+pub fn pattern_var(name: Node) -> Option<Node> {
+    match name.kind() {
+        "var" => Some(name),
+        "quote" => named_children(name)
+            .into_iter()
+            .next()
+            .filter(|n| n.kind() == "var"),
+        _ => None,
+    }
+}
+
+const BINARY_OPERATORS: &[&str] = &[
+    "or", "and", "matches", "==", "!=", "<", "<=", ">", ">=", "++", "--", "+", "-", "%%", "*", "/",
+    "%", "\\/", "/\\",
+];
+
+/// Split a binary expression such as `a + b` into its operands and operator token.
+pub fn binary_parts(node: Node) -> Option<(Node, &'static str, Node)> {
+    let operands = named_children(node);
+    let [left, right] = operands[..] else {
+        return None;
+    };
+    let mut cursor = node.walk();
+    let op = node
+        .children(&mut cursor)
+        .filter(|n| {
+            !n.is_named() && n.start_byte() >= left.end_byte() && n.end_byte() <= right.start_byte()
+        })
+        .map(|n| n.kind())
+        .find(|k| BINARY_OPERATORS.contains(k))?;
+    Some((left, op, right))
+}
+
+/// Strip grouping around a process: `(P)` and `{ P }` both mean `P`.
+pub fn unwrap_group(mut node: Node) -> Node {
+    while matches!(node.kind(), "block" | "parenthesized") {
+        match named_children(node)[..] {
+            [inner] => node = inner,
+            _ => break,
+        }
+    }
+    node
+}
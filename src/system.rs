@@ -0,0 +1,218 @@
+use std::sync::OnceLock;
+
+use serde_json::Value;
+use tower_lsp::lsp_types::*;
+use tree_sitter::{Node, Point};
+
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::scope::{BinderKind, Scopes};
+use crate::syntax::{self, is_send, named_children, send_args, send_channel};
+
+const CATALOG: &str = include_str!("../catalog/system-channels.json");
+
+/// A system process reachable through a `rho:` URI.
+pub struct SystemChannel {
+    pub uri: String,
+    /// The name the channel is conventionally bound to (`stdout` for `rho:io:stdout`).
+    pub name: String,
+    /// Parameters of a send on the channel; `None` for URIs that are not sent to
+    /// directly (registry entries, deployer ids).
+    pub params: Option<Vec<String>>,
+    pub doc: String,
+}
+
+impl SystemChannel {
+    pub fn signature(&self) -> String {
+        match &self.params {
+            Some(params) => format!("{}!({})", self.uri, params.join(", ")),
+            None => self.uri.clone(),
+        }
+    }
+
+    pub fn markdown(&self, version: &str) -> String {
+        format!(
+            "```rholang\n{}\n```\n\n{}\n\n*System channel ({version})*",
+            self.signature(),
+            self.doc
+        )
+    }
+}
+
+pub struct Catalog {
+    pub version: String,
+    pub channels: Vec<SystemChannel>,
+}
+
+impl Catalog {
+    pub fn lookup(&self, uri: &str) -> Option<&SystemChannel> {
+        self.channels.iter().find(|c| c.uri == uri)
+    }
+
+    /// Channels conventionally bound to a name spelled `name`.
+    pub fn by_name<'c>(&'c self, name: &'c str) -> impl Iterator<Item = &'c SystemChannel> {
+        self.channels.iter().filter(move |c| c.name == name)
+    }
+}
+
+/// The bundled system channel catalog.
+pub fn catalog() -> &'static Catalog {
+    static CELL: OnceLock<Catalog> = OnceLock::new();
+    CELL.get_or_init(|| {
+        let json: Value = serde_json::from_str(CATALOG).expect("invalid system channel catalog");
+        let str_of = |v: &Value, key: &str| v[key].as_str().unwrap_or_default().to_string();
+        Catalog {
+            version: str_of(&json, "version"),
+            channels: json["channels"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|c| SystemChannel {
+                    uri: str_of(c, "uri"),
+                    name: str_of(c, "name"),
+                    params: c["params"].as_array().map(|ps| {
+                        ps.iter()
+                            .filter_map(|p| p.as_str().map(str::to_string))
+                            .collect()
+                    }),
+                    doc: str_of(c, "doc"),
+                })
+                .collect(),
+        }
+    })
+}
+
+/// The URI inside a `uri_literal`, without backticks.
+pub fn uri_text<'a>(node: Node, source: &'a str) -> &'a str {
+    syntax::text(node, source).trim_matches('`')
+}
+
+/// The system URI a `new x(`rho:...`)` name is bound to, for any use or the declaration of `x`.
+pub fn bound_uri<'a>(scopes: &Scopes, var: Node, source: &'a str) -> Option<&'a str> {
+    let binder = &scopes.binders[scopes.binder_of(var)?];
+    if binder.kind != BinderKind::New {
+        return None;
+    }
+    let decl = binder.node.parent()?;
+    let mut cursor = decl.walk();
+    let uri = decl
+        .children(&mut cursor)
+        .find(|c| c.kind() == "uri_literal")?;
+    Some(uri_text(uri, source))
+}
+
+/// Hover documentation for a system URI literal or a name bound to one.
+pub fn hover_doc(doc: &Document, scopes: &Scopes, node: Node) -> Option<String> {
+    let uri = match node.kind() {
+        "uri_literal" => uri_text(node, &doc.source),
+        "var" => bound_uri(scopes, node, &doc.source)?,
+        _ => return None,
+    };
+    let catalog = catalog();
+    Some(catalog.lookup(uri)?.markdown(&catalog.version))
+}
+
+/// Warn about unknown `rho:` URIs and sends on system channels with the wrong arity.
+pub fn check(doc: &Document, scopes: &Scopes, diagnostics: &mut Vec<Diagnostic>) {
+    walk(doc.tree.root_node(), &doc.source, scopes, diagnostics);
+}
+
+fn walk(node: Node, source: &str, scopes: &Scopes, diagnostics: &mut Vec<Diagnostic>) {
+    let catalog = catalog();
+    if node.kind() == "uri_literal" {
+        let uri = uri_text(node, source);
+        // `rho:id:` registry ids are validated separately
+        if uri.starts_with("rho:") && !uri.starts_with("rho:id:") && catalog.lookup(uri).is_none() {
+            diagnostics.push(Diagnostic {
+                range: node_range(node),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("unknown-system-uri".into())),
+                source: Some("rholang-lsp".into()),
+                message: format!("Unknown system URI `{uri}` (catalog {})", catalog.version),
+                ..Default::default()
+            });
+        }
+    } else if is_send(node) {
+        let channel = send_channel(node).filter(|c| c.kind() == "var");
+        let system = channel
+            .and_then(|c| bound_uri(scopes, c, source))
+            .and_then(|uri| catalog.lookup(uri));
+        if let (Some(channel), Some(system)) = (channel, system) {
+            let argc = send_args(node).len();
+            let message = match &system.params {
+                Some(params) if params.len() != argc => Some(format!(
+                    "`{}` expects {} argument(s) ({}), found {argc}",
+                    system.uri,
+                    params.len(),
+                    params.join(", ")
+                )),
+                None => Some(format!("`{}` is not a callable system channel", system.uri)),
+                _ => None,
+            };
+            if let Some(message) = message {
+                diagnostics.push(Diagnostic {
+                    range: node_range(channel),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: Some(NumberOrString::String("system-arity".into())),
+                    source: Some("rholang-lsp".into()),
+                    message,
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    for child in named_children(node) {
+        walk(child, source, scopes, diagnostics);
+    }
+}
+
+/// Signature help inside the argument list of a send on a system channel.
+pub fn signature_help(doc: &Document, pos: Position) -> Option<SignatureHelp> {
+    let point = Point {
+        row: pos.line as usize,
+        column: pos.character as usize,
+    };
+    let offset = doc.offset_at(pos.line as usize, pos.character as usize);
+    let mut node = doc
+        .tree
+        .root_node()
+        .named_descendant_for_point_range(point, point)?;
+    while !is_send(node) {
+        node = node.parent()?;
+    }
+
+    let scopes = Scopes::analyze(doc);
+    let channel = send_channel(node).filter(|c| c.kind() == "var")?;
+    if offset <= channel.end_byte() {
+        return None;
+    }
+    let system = catalog().lookup(bound_uri(&scopes, channel, &doc.source)?)?;
+    let params = system.params.as_ref()?;
+
+    let active = send_args(node)
+        .iter()
+        .filter(|arg| arg.end_byte() < offset)
+        .count()
+        .min(params.len().saturating_sub(1));
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label: system.signature(),
+            documentation: Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: system.doc.clone(),
+            })),
+            parameters: Some(
+                params
+                    .iter()
+                    .map(|p| ParameterInformation {
+                        label: ParameterLabel::Simple(p.clone()),
+                        documentation: None,
+                    })
+                    .collect(),
+            ),
+            active_parameter: Some(active as u32),
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active as u32),
+    })
+}
@@ -0,0 +1,33 @@
+use tower_lsp::lsp_types::*;
+use tree_sitter::Parser;
+
+use crate::document::Document;
+
+pub fn parse(source: &str) -> Document {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_rholang::LANGUAGE.into())
+        .expect("Failed to load Rholang tree-sitter grammar");
+    Document::new(&mut parser, source.to_string()).expect("parse")
+}
+
+pub fn uri() -> Url {
+    Url::parse("file:///test.rho").unwrap()
+}
+
+/// The document's text after applying the edits `edit` makes to it.
+pub fn apply(doc: &Document, edit: &WorkspaceEdit) -> String {
+    let mut edits: Vec<&TextEdit> = edit
+        .changes
+        .iter()
+        .flat_map(|changes| changes.values())
+        .flatten()
+        .collect();
+    let offset = |p: Position| doc.offset_at(p.line as usize, p.character as usize);
+    edits.sort_by_key(|e| std::cmp::Reverse((offset(e.range.start), offset(e.range.end))));
+    let mut text = doc.source.clone();
+    for e in edits {
+        text.replace_range(offset(e.range.start)..offset(e.range.end), &e.new_text);
+    }
+    text
+}
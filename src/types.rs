@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::flow::{self, Facts, Sites};
+use crate::methods::{self, Returns};
+use crate::scope::{Scopes, Sort};
+use crate::syntax::{self, binary_parts, named_children, unwrap_group};
+
+/// Ground types of Rholang processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Int,
+    String,
+    Bool,
+    ByteArray,
+    Uri,
+    List,
+    Map,
+    Set,
+    Tuple,
+    Nil,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Type::Int => "Int",
+            Type::String => "String",
+            Type::Bool => "Bool",
+            Type::ByteArray => "ByteArray",
+            Type::Uri => "Uri",
+            Type::List => "List",
+            Type::Map => "Map",
+            Type::Set => "Set",
+            Type::Tuple => "Tuple",
+            Type::Nil => "Nil",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Types inferred for the processes bound by `let` and by receives fed only by
+/// literal sends.
+pub struct Inference<'s, 'a> {
+    scopes: &'s Scopes<'a>,
+    source: &'a str,
+    /// Type of the process each binder stands for (for names: the process `*x` yields).
+    bound: HashMap<usize, Type>,
+}
+
+impl<'s, 'a> Inference<'s, 'a> {
+    pub fn new(doc: &'a Document, scopes: &'s Scopes<'a>) -> Self {
+        let mut inference = Inference {
+            scopes,
+            source: &doc.source,
+            bound: HashMap::new(),
+        };
+        let sites = Sites::collect(doc.tree.root_node(), scopes);
+        flow::propagate(&sites, scopes, &mut inference);
+        inference
+    }
+
+    /// The type of the process bound to a variable, whatever its sort.
+    pub fn bound_type(&self, var: Node) -> Option<Type> {
+        self.scopes
+            .binder_of(var)
+            .and_then(|b| self.bound.get(&b).copied())
+    }
+
+    /// The ground type of a process expression, if it can be inferred locally.
+    pub fn type_of(&self, node: Node) -> Option<Type> {
+        let node = unwrap_group(node);
+        match node.kind() {
+            "long_literal" => return Some(Type::Int),
+            "string_literal" => return Some(Type::String),
+            "bool_literal" => return Some(Type::Bool),
+            "uri_literal" => return Some(Type::Uri),
+            "nil" => return Some(Type::Nil),
+            "list" => return Some(Type::List),
+            "map" => return Some(Type::Map),
+            "set" => return Some(Type::Set),
+            "tuple" => return Some(Type::Tuple),
+            "collection" => {
+                return named_children(node)
+                    .into_iter()
+                    .next()
+                    .and_then(|c| self.type_of(c))
+            }
+            "not" => return Some(Type::Bool),
+            "neg" => return Some(Type::Int),
+            "var" => {
+                let binder = self.scopes.binder_of(node)?;
+                if self.scopes.binders[binder].sort == Sort::Process {
+                    return self.bound.get(&binder).copied();
+                }
+                return None;
+            }
+            "method" => {
+                let name = node.child_by_field_name("name")?;
+                let method = methods::lookup(syntax::text(name, self.source))?;
+                return match method.returns {
+                    Returns::Type(ty) => Some(ty),
+                    Returns::Receiver => self.type_of(node.child_by_field_name("receiver")?),
+                    Returns::Element => None,
+                };
+            }
+            "eval" => {
+                let var = named_children(node).into_iter().next()?;
+                let binder = self.scopes.binder_of(var)?;
+                if self.scopes.binders[binder].sort == Sort::Name {
+                    return self.bound.get(&binder).copied();
+                }
+                return None;
+            }
+            _ => {}
+        }
+        let (left, op, _) = binary_parts(node)?;
+        match op {
+            "+" | "-" | "*" | "/" | "%" => Some(Type::Int),
+            "==" | "!=" | "<" | "<=" | ">" | ">=" | "and" | "or" | "matches" => Some(Type::Bool),
+            "%%" => Some(Type::String),
+            "++" | "--" => self.type_of(left),
+            _ => None,
+        }
+    }
+}
+
+impl Facts<Type> for Inference<'_, '_> {
+    fn value_of(&self, node: Node) -> Option<Type> {
+        self.type_of(node)
+    }
+
+    fn set(&mut self, var: Node, ty: Type) -> bool {
+        match self.scopes.binder_of(var) {
+            Some(b) => self.bound.insert(b, ty) != Some(ty),
+            None => false,
+        }
+    }
+}
+
+/// The operand types an operator accepts, and whether both sides must agree.
+fn accepted(op: &str) -> Option<(&'static [Type], bool)> {
+    Some(match op {
+        "+" | "-" | "*" | "/" | "%" => (&[Type::Int], false),
+        "<" | "<=" | ">" | ">=" => (&[Type::Int, Type::String], true),
+        "and" | "or" => (&[Type::Bool], false),
+        "++" => (
+            &[
+                Type::String,
+                Type::ByteArray,
+                Type::List,
+                Type::Map,
+                Type::Set,
+            ],
+            true,
+        ),
+        _ => return None,
+    })
+}
+
+/// Report operands and conditions whose inferred type the operation cannot accept.
+pub fn check(doc: &Document, inference: &Inference, diagnostics: &mut Vec<Diagnostic>) {
+    walk(doc.tree.root_node(), inference, diagnostics);
+}
+
+fn walk(node: Node, inference: &Inference, diagnostics: &mut Vec<Diagnostic>) {
+    let mut report = |at: Node, message: String| {
+        diagnostics.push(Diagnostic {
+            range: node_range(at),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String("type-mismatch".into())),
+            source: Some("rholang-lsp".into()),
+            message,
+            ..Default::default()
+        });
+    };
+
+    if let Some((left, op, right)) = binary_parts(node) {
+        let lt = inference.type_of(left);
+        let rt = inference.type_of(right);
+        if let Some((types, same)) = accepted(op) {
+            let expected = types
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(" or ");
+            for (operand, ty) in [(left, lt), (right, rt)] {
+                if let Some(ty) = ty.filter(|t| !types.contains(t)) {
+                    report(operand, format!("`{op}` expects {expected}, found {ty}"));
+                }
+            }
+            if let (true, Some(l), Some(r)) = (same, lt, rt) {
+                if l != r && types.contains(&l) && types.contains(&r) {
+                    report(
+                        node,
+                        format!("`{op}` operands must have the same type, found {l} and {r}"),
+                    );
+                }
+            }
+        } else if op == "%%" {
+            if let Some(ty) = lt.filter(|t| *t != Type::String) {
+                report(left, format!("`%%` expects a String template, found {ty}"));
+            }
+            if let Some(ty) = rt.filter(|t| *t != Type::Map) {
+                report(right, format!("`%%` expects a Map of values, found {ty}"));
+            }
+        } else if op == "--" {
+            if let Some(ty) = lt.filter(|t| !matches!(t, Type::Set | Type::Map)) {
+                report(left, format!("`--` expects a Set or Map, found {ty}"));
+            }
+        }
+    } else {
+        match node.kind() {
+            "ifElse" => {
+                if let Some(cond) = node.child_by_field_name("condition") {
+                    if let Some(ty) = inference.type_of(cond).filter(|t| *t != Type::Bool) {
+                        report(cond, format!("`if` condition must be a Bool, found {ty}"));
+                    }
+                }
+            }
+            "not" | "neg" => {
+                let (expected, op) = if node.kind() == "not" {
+                    (Type::Bool, "not")
+                } else {
+                    (Type::Int, "-")
+                };
+                if let Some(operand) = named_children(node).into_iter().next() {
+                    if let Some(ty) = inference.type_of(operand).filter(|t| *t != expected) {
+                        report(operand, format!("`{op}` expects {expected}, found {ty}"));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for child in named_children(node) {
+        walk(child, inference, diagnostics);
+    }
+}
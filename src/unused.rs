@@ -0,0 +1,139 @@
+use std::collections::{HashMap, HashSet};
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::code_actions::{attach_fix, edit_action, remove_items, single_file_edit, unwrap_edits};
+use crate::diagnostics::node_range;
+use crate::document::Document;
+use crate::scope::{BinderKind, Scopes};
+use crate::syntax::{decl_values, let_decls, named_children, names_list, pattern_var};
+
+/// Kind of the source action removing every unused declaration in a file.
+pub const REMOVE_UNUSED: CodeActionKind = CodeActionKind::new("source.removeUnused");
+
+/// `new` and `let` binders nothing refers to.
+fn unused_binders(scopes: &Scopes) -> Vec<usize> {
+    (0..scopes.binders.len())
+        .filter(|&b| matches!(scopes.binders[b].kind, BinderKind::New | BinderKind::Let))
+        .filter(|&b| scopes.uses_of(b).next().is_none())
+        .collect()
+}
+
+/// The `new` or `let` that introduces a binder.
+fn container(node: Node) -> Option<Node> {
+    let mut node = node.parent()?;
+    while !matches!(node.kind(), "new" | "let") {
+        node = node.parent()?;
+    }
+    Some(node)
+}
+
+/// Edits deleting the declarations of `binders`, unwrapping any `new` or `let` left
+/// with nothing to declare.
+fn removal_edits(scopes: &Scopes, binders: &[usize]) -> Vec<TextEdit> {
+    let removed: HashSet<usize> = binders
+        .iter()
+        .map(|&b| scopes.binders[b].node.id())
+        .collect();
+    let mut containers: HashMap<usize, Node> = HashMap::new();
+    for &b in binders {
+        if let Some(c) = container(scopes.binders[b].node) {
+            containers.insert(c.id(), c);
+        }
+    }
+
+    let mut edits = Vec::new();
+    for node in containers.into_values() {
+        if node.kind() == "new" {
+            let decls: Vec<Node> = node
+                .child_by_field_name("decls")
+                .map(named_children)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|d| d.kind() == "name_decl")
+                .collect();
+            let flags: Vec<bool> = decls
+                .iter()
+                .map(|d| d.named_child(0).is_some_and(|v| removed.contains(&v.id())))
+                .collect();
+            match node.child_by_field_name("proc") {
+                Some(body) if flags.iter().all(|f| *f) => edits.extend(unwrap_edits(node, body)),
+                _ => edits.extend(remove_items(&decls, &flags)),
+            }
+        } else {
+            let decls = node
+                .child_by_field_name("decls")
+                .map(let_decls)
+                .unwrap_or_default();
+            let mut decl_flags = Vec::new();
+            for decl in &decls {
+                let names = decl
+                    .child_by_field_name("names")
+                    .map(names_list)
+                    .unwrap_or_default();
+                let flags: Vec<bool> = names
+                    .iter()
+                    .map(|n| pattern_var(*n).is_some_and(|v| removed.contains(&v.id())))
+                    .collect();
+                let values = decl_values(*decl);
+                let all = !flags.is_empty() && flags.iter().all(|f| *f);
+                // Partial removal only when names and values pair up one to one
+                if !all && values.len() == names.len() {
+                    edits.extend(remove_items(&names, &flags));
+                    edits.extend(remove_items(&values, &flags));
+                }
+                decl_flags.push(all);
+            }
+            let body = node
+                .child_by_field_name("proc")
+                .or_else(|| node.child_by_field_name("body"));
+            match body {
+                Some(body) if decl_flags.iter().all(|f| *f) => {
+                    edits.extend(unwrap_edits(node, body))
+                }
+                _ => edits.extend(remove_items(&decls, &decl_flags)),
+            }
+        }
+    }
+    edits
+}
+
+/// Hint at unused `new` names and `let` bindings, with a fix deleting each one.
+pub fn check(scopes: &Scopes, diagnostics: &mut Vec<Diagnostic>) {
+    for b in unused_binders(scopes) {
+        let binder = &scopes.binders[b];
+        let edits = removal_edits(scopes, &[b]);
+        let mut diagnostic = Diagnostic {
+            range: node_range(binder.node),
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::String("unused-declaration".into())),
+            source: Some("rholang-lsp".into()),
+            message: format!("`{}` is never used", binder.name),
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            ..Default::default()
+        };
+        if !edits.is_empty() {
+            attach_fix(
+                &mut diagnostic,
+                format!("Remove unused declaration `{}`", binder.name),
+                edits,
+            );
+        }
+        diagnostics.push(diagnostic);
+    }
+}
+
+/// Source action deleting every unused declaration in the document.
+pub fn remove_all(doc: &Document, uri: &Url) -> Option<CodeActionOrCommand> {
+    let scopes = Scopes::analyze(doc);
+    let edits = removal_edits(&scopes, &unused_binders(&scopes));
+    if edits.is_empty() {
+        return None;
+    }
+    Some(edit_action(
+        "Remove all unused declarations",
+        REMOVE_UNUSED,
+        single_file_edit(uri, edits),
+    ))
+}